@@ -0,0 +1,555 @@
+use percent_encoding::percent_decode_str;
+use serde_json::{json, Value};
+use tiny_http::{Header, Request, Response, Server};
+
+use crate::{
+    data::Attacks,
+    matchup::{evaluate, Matchup},
+    query::{self, find_weapon, find_weapon_class, LoadoutQuery, QueryError},
+    static_data::{INNATE_WEAPON_POISE, POISE_DATA, WEAPON_CLASSES},
+};
+
+/// Paths of the API, the ones with a weapon name end with `/`
+const PATHS: [&str; 8] = [
+    "openapi.json",
+    "weapons",
+    "weapons/",
+    "classes",
+    "attacks",
+    "hyperarmor",
+    "staggers-me",
+    "trade",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl ApiResponse {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl ToString) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.to_string() }),
+        }
+    }
+}
+
+impl From<QueryError> for ApiResponse {
+    fn from(error: QueryError) -> Self {
+        match error {
+            QueryError::UnknownWeapon(_) => ApiResponse::error(404, error),
+            _ => ApiResponse::error(400, error),
+        }
+    }
+}
+
+/// Query string parameters, named like the options of hyperarmor-cli
+struct Params(Vec<(String, String)>);
+
+impl Params {
+    fn parse(query: &str) -> Self {
+        Self(
+            form_urlencoded::parse(query.as_bytes())
+                .map(|(name, value)| (name.into_owned(), value.into_owned()))
+                .collect(),
+        )
+    }
+
+    fn values(&self, name: &str) -> Vec<&String> {
+        self.0
+            .iter()
+            .filter(|(param, _)| param == name)
+            .map(|(_, value)| value)
+            .collect()
+    }
+
+    fn value(&self, name: &str) -> Option<&String> {
+        self.values(name).last().copied()
+    }
+
+    /// Given without a value or with anything but `false` and `0`
+    fn flag(&self, name: &str) -> bool {
+        self.value(name)
+            .is_some_and(|value| value != "false" && value != "0")
+    }
+
+    fn number<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, ApiResponse> {
+        self.value(name)
+            .map(|value| {
+                value.parse().map_err(|_| {
+                    ApiResponse::error(400, format!("Invalid number {} for {}", value, name))
+                })
+            })
+            .transpose()
+    }
+
+    fn loadout_query(&self) -> Result<LoadoutQuery, ApiResponse> {
+        Ok(LoadoutQuery {
+            code: self.value("loadout").cloned(),
+            weapon: self.value("weapon").cloned(),
+            attack: self.value("attack").cloned(),
+            armor_poise: self.number("armor-poise")?,
+            modifiers: self.values("modifier").into_iter().cloned().collect(),
+            is_bull_goat: self.flag("bullgoat"),
+            is_in_recovery: self.flag("recovery"),
+        })
+    }
+}
+
+fn weapons(params: &Params) -> Result<Value, ApiResponse> {
+    let weapon_class = params
+        .value("class")
+        .map(|weapon_class| find_weapon_class(weapon_class))
+        .transpose()?;
+
+    Ok(POISE_DATA
+        .values()
+        .filter(|weapon| match weapon_class {
+            Some(weapon_class) => &weapon.class == weapon_class,
+            None => true,
+        })
+        .map(|weapon| {
+            json!({
+                "name": weapon.name,
+                "class": weapon.class,
+                "innate_weapon_poise": INNATE_WEAPON_POISE.get(&weapon.name),
+            })
+        })
+        .collect())
+}
+
+/// Poise damage per hit of every attack the weapon has
+fn weapon(name: &str) -> Result<Value, ApiResponse> {
+    let weapon = &POISE_DATA[find_weapon(&POISE_DATA, name)?];
+
+    let attacks = weapon
+        .attacks()
+        .map(|(attack, poise_damage_values)| {
+            json!({
+                "attack": format!("{:?}", attack),
+                "label": attack.to_string(),
+                "poise_damage": poise_damage_values.0,
+                "total": poise_damage_values.total(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "name": weapon.name,
+        "class": weapon.class,
+        "innate_weapon_poise": INNATE_WEAPON_POISE.get(&weapon.name),
+        "attacks": attacks,
+    }))
+}
+
+fn attacks() -> Value {
+    Attacks::all()
+        .iter()
+        .map(|attack| {
+            json!({
+                "name": format!("{:?}", attack),
+                "label": attack.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn hyperarmor(params: &Params) -> Result<Value, ApiResponse> {
+    let equipment_view = params.loadout_query()?.equipment_view()?;
+
+    Ok(query::hyperarmor_table(&equipment_view)?.to_json_value())
+}
+
+fn staggers_me(params: &Params) -> Result<Value, ApiResponse> {
+    let equipment_view = params.loadout_query()?.equipment_view()?;
+
+    let table = query::staggers_me_report(
+        &equipment_view,
+        params.number("max-hits")?.unwrap_or(1),
+        params
+            .value("class")
+            .map(|weapon_class| weapon_class.as_str()),
+    )?;
+
+    Ok(table.to_json_value())
+}
+
+fn trade(body: &str) -> Result<Value, ApiResponse> {
+    let matchup = serde_json::from_str::<Matchup>(body)
+        .map_err(|error| ApiResponse::error(400, format!("Invalid matchup: {}", error)))?;
+
+    serde_json::to_value(evaluate(&POISE_DATA, &matchup)?)
+        .map_err(|error| ApiResponse::error(500, error))
+}
+
+/// Answers a request without any networking, `url` is the path with the query string
+pub fn handle(method: &str, url: &str, body: &str) -> ApiResponse {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let params = Params::parse(query);
+
+    let segments = path
+        .trim_matches('/')
+        .split('/')
+        .map(|segment| percent_decode_str(segment).decode_utf8_lossy().into_owned())
+        .collect::<Vec<_>>();
+    let segments = segments
+        .iter()
+        .map(|segment| segment.as_str())
+        .collect::<Vec<_>>();
+
+    let result = match (method, segments.as_slice()) {
+        ("GET", ["openapi.json"]) => Ok(openapi()),
+        ("GET", ["weapons"]) => weapons(&params),
+        ("GET", ["weapons", name]) => weapon(name),
+        ("GET", ["classes"]) => {
+            Ok(query::classes_table(&WEAPON_CLASSES, &INNATE_WEAPON_POISE).to_json_value())
+        }
+        ("GET", ["attacks"]) => Ok(attacks()),
+        ("GET", ["hyperarmor"]) => hyperarmor(&params),
+        ("GET", ["staggers-me"]) => staggers_me(&params),
+        ("POST", ["trade"]) => trade(body),
+        // CORS preflight of browsers, answered with the headers of every response
+        ("OPTIONS", _) => {
+            return ApiResponse {
+                status: 204,
+                body: Value::Null,
+            }
+        }
+        (_, [path]) if PATHS.contains(path) => Err(ApiResponse::error(405, "Method not allowed")),
+        (_, ["weapons", _]) => Err(ApiResponse::error(405, "Method not allowed")),
+        _ => Err(ApiResponse::error(404, format!("Unknown path {}", path))),
+    };
+
+    result.map(ApiResponse::ok).unwrap_or_else(|error| error)
+}
+
+fn respond(mut request: Request) {
+    let mut body = String::new();
+
+    if let Err(error) = request.as_reader().read_to_string(&mut body) {
+        log::warn!("Failed to read request body: {}", error);
+    }
+
+    let response = handle(request.method().as_str(), request.url(), &body);
+
+    let text = match response.body {
+        Value::Null => String::new(),
+        body => serde_json::to_string_pretty(&body).unwrap_or_default(),
+    };
+
+    let mut http_response = Response::from_string(text).with_status_code(response.status);
+
+    for (name, value) in [
+        ("Content-Type", "application/json"),
+        ("Access-Control-Allow-Origin", "*"),
+        ("Access-Control-Allow-Methods", "GET, POST, OPTIONS"),
+        ("Access-Control-Allow-Headers", "Content-Type"),
+    ] {
+        if let Ok(header) = Header::from_bytes(name, value) {
+            http_response.add_header(header);
+        }
+    }
+
+    if let Err(error) = request.respond(http_response) {
+        log::warn!("Failed to respond: {}", error);
+    }
+}
+
+/// Answers requests one at a time until the process stops
+pub fn serve(server: &Server) {
+    for request in server.incoming_requests() {
+        respond(request);
+    }
+}
+
+/// OpenAPI 3 description of `handle`
+pub fn openapi() -> Value {
+    let loadout_parameters = json!([
+        { "$ref": "#/components/parameters/loadout" },
+        { "$ref": "#/components/parameters/weapon" },
+        { "$ref": "#/components/parameters/attack" },
+        { "$ref": "#/components/parameters/armor-poise" },
+        { "$ref": "#/components/parameters/modifier" },
+        { "$ref": "#/components/parameters/bullgoat" },
+        { "$ref": "#/components/parameters/recovery" },
+    ]);
+
+    let response = |description: &str, schema: Value| {
+        json!({
+            "200": {
+                "description": description,
+                "content": { "application/json": { "schema": schema } },
+            },
+            "400": { "$ref": "#/components/responses/Error" },
+            "404": { "$ref": "#/components/responses/Error" },
+        })
+    };
+
+    let query_parameter = |name: &str, description: &str, schema: Value| {
+        json!({
+            "name": name,
+            "in": "query",
+            "description": description,
+            "schema": schema,
+        })
+    };
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Hyperarmor Inspector API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Poise data and calculations of hyperarmor_inspector. Tables are the ones printed by hyperarmor-cli --json.",
+        },
+        "paths": {
+            "/openapi.json": {
+                "get": {
+                    "summary": "This schema",
+                    "responses": response("OpenAPI schema", json!({ "type": "object" })),
+                },
+            },
+            "/weapons": {
+                "get": {
+                    "summary": "Every weapon",
+                    "parameters": [query_parameter("class", "Only weapons of a class", json!({ "type": "string" }))],
+                    "responses": response("Weapons", json!({
+                        "type": "array",
+                        "items": { "$ref": "#/components/schemas/Weapon" },
+                    })),
+                },
+            },
+            "/weapons/{name}": {
+                "get": {
+                    "summary": "Poise damage per hit of every attack of a weapon",
+                    "parameters": [{
+                        "name": "name",
+                        "in": "path",
+                        "required": true,
+                        "description": "Weapon name, ignoring case, or a part only one weapon contains",
+                        "schema": { "type": "string" },
+                    }],
+                    "responses": response("Weapon poise data", json!({ "$ref": "#/components/schemas/WeaponPoiseDamage" })),
+                },
+            },
+            "/classes": {
+                "get": {
+                    "summary": "Weapon classes with their weapon count and innate weapon poise",
+                    "responses": response("Classes table", json!({ "$ref": "#/components/schemas/Table" })),
+                },
+            },
+            "/attacks": {
+                "get": {
+                    "summary": "Every attack",
+                    "responses": response("Attacks", json!({
+                        "type": "array",
+                        "items": { "$ref": "#/components/schemas/Attack" },
+                    })),
+                },
+            },
+            "/hyperarmor": {
+                "get": {
+                    "summary": "Hyperarmor and poise of a loadout during its attack",
+                    "parameters": loadout_parameters,
+                    "responses": response("Hyperarmor table", json!({ "$ref": "#/components/schemas/Table" })),
+                },
+            },
+            "/staggers-me": {
+                "get": {
+                    "summary": "Every attack that staggers a loadout, largest margin first",
+                    "parameters": loadout_parameters
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .cloned()
+                        .chain([
+                            query_parameter("max-hits", "Hits an attack may take to stagger, 1 by default", json!({ "type": "integer", "minimum": 1 })),
+                            query_parameter("class", "Only weapons of a class", json!({ "type": "string" })),
+                        ])
+                        .collect::<Vec<_>>(),
+                    "responses": response("What Staggers Me table", json!({ "$ref": "#/components/schemas/Table" })),
+                },
+            },
+            "/trade": {
+                "post": {
+                    "summary": "Both attacks hit at the same time, who is staggered",
+                    "requestBody": {
+                        "required": true,
+                        "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Matchup" } } },
+                    },
+                    "responses": response("Trade", json!({ "$ref": "#/components/schemas/MatchupResult" })),
+                },
+            },
+        },
+        "components": {
+            "parameters": {
+                "loadout": query_parameter("loadout", "Loadout code copied from the loadout tabs, the other parameters take precedence", json!({ "type": "string" })),
+                "weapon": query_parameter("weapon", "Your weapon", json!({ "type": "string" })),
+                "attack": query_parameter("attack", "Your attack, like TwoHandedR1Chain(0)", json!({ "type": "string" })),
+                "armor-poise": query_parameter("armor-poise", "Poise of your armor", json!({ "type": "integer", "minimum": 0 })),
+                "modifier": query_parameter("modifier", "Can be given several times", json!({ "type": "string" })),
+                "bullgoat": query_parameter("bullgoat", "Bull-Goat's Talisman", json!({ "type": "boolean" })),
+                "recovery": query_parameter("recovery", "In recovery from a previous attack", json!({ "type": "boolean" })),
+            },
+            "responses": {
+                "Error": {
+                    "description": "Unknown weapon, attack or path, or invalid parameters",
+                    "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } },
+                },
+            },
+            "schemas": {
+                "Error": {
+                    "type": "object",
+                    "properties": { "error": { "type": "string" } },
+                },
+                "Weapon": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "class": { "type": "string" },
+                        "innate_weapon_poise": { "type": "integer", "nullable": true },
+                    },
+                },
+                "Attack": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "example": "TwoHandedR1Chain(0)" },
+                        "label": { "type": "string", "example": "Two Handed R1 Chain(0)" },
+                    },
+                },
+                "WeaponPoiseDamage": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "class": { "type": "string" },
+                        "innate_weapon_poise": { "type": "integer", "nullable": true },
+                        "attacks": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "attack": { "type": "string" },
+                                    "label": { "type": "string" },
+                                    "poise_damage": { "type": "array", "items": { "type": "integer" } },
+                                    "total": { "type": "integer" },
+                                },
+                            },
+                        },
+                    },
+                },
+                "Table": {
+                    "type": "object",
+                    "properties": {
+                        "title": { "type": "string" },
+                        "metadata": { "type": "object", "additionalProperties": { "type": "string" } },
+                        "columns": { "type": "array", "items": { "type": "string" } },
+                        "rows": {
+                            "type": "array",
+                            "items": { "type": "object", "additionalProperties": { "type": "string" } },
+                        },
+                    },
+                },
+                "Matchup": {
+                    "type": "object",
+                    "required": ["my_weapon", "my_attack", "armor_poise", "their_weapon", "their_attack"],
+                    "properties": {
+                        "my_weapon": { "type": "string" },
+                        "my_attack": { "type": "string" },
+                        "armor_poise": { "type": "integer", "minimum": 0 },
+                        "modifiers": { "type": "string", "description": "Separated by ;" },
+                        "their_weapon": { "type": "string" },
+                        "their_attack": { "type": "string" },
+                        "their_armor_poise": { "type": "integer", "minimum": 0 },
+                    },
+                },
+                "MatchupResult": {
+                    "type": "object",
+                    "properties": {
+                        "hyperarmor": { "type": "number" },
+                        "incoming_poise_damage": { "type": "number" },
+                        "poise": { "type": "number" },
+                        "outcome": { "$ref": "#/components/schemas/ThresholdStatus" },
+                        "margin": { "type": "number" },
+                        "outgoing_poise_damage": { "type": "number" },
+                        "their_poise": { "type": "number" },
+                        "their_outcome": { "$ref": "#/components/schemas/ThresholdStatus" },
+                        "trade": { "type": "string", "enum": ["Win", "Loss", "BothStaggered", "NeitherStaggered"] },
+                    },
+                },
+                "ThresholdStatus": {
+                    "type": "string",
+                    "enum": ["Staggered", "StaggeredByTotal", "Tanked"],
+                },
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_handle() {
+        let response = handle("GET", "/attacks", "");
+
+        assert_eq!(response.status, 200);
+        assert!(response.body.as_array().unwrap().contains(
+            &json!({ "name": "TwoHandedR1Chain(0)", "label": "Two Handed R1 Chain(0)" })
+        ));
+
+        assert_eq!(handle("GET", "/nope", "").status, 404);
+        assert_eq!(handle("DELETE", "/attacks", "").status, 405);
+        assert_eq!(handle("GET", "/trade", "").status, 405);
+        assert_eq!(handle("POST", "/trade", "not json").status, 400);
+        assert_eq!(handle("OPTIONS", "/trade", "").status, 204);
+    }
+
+    #[test]
+    fn test_openapi() {
+        let openapi = openapi();
+        let paths = openapi["paths"].as_object().unwrap();
+
+        for path in PATHS {
+            let path = match path.ends_with('/') {
+                true => format!("/{}{{name}}", path),
+                false => format!("/{}", path),
+            };
+
+            assert!(paths.contains_key(&path), "{} is missing", path);
+        }
+    }
+
+    #[test]
+    fn test_serve() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let address = server.server_addr().to_ip().unwrap();
+
+        std::thread::spawn(move || serve(&server));
+
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "GET /attacks HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+            address
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("Content-Type: application/json"));
+        assert!(response.contains("\"TwoHandedR1Chain(0)\""));
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    loadouts_view::LoadoutState, one_attack_plot_view::OneAttackPlotViewState,
+    poise_data_view::PoiseDataViewState,
+};
+
+pub const APP_STATE_KEY: &str = "hyperarmor_inspector_state";
+
+/// Bumped whenever `AppState` changes in a way older states can't be read as
+pub const APP_STATE_VERSION: u32 = 3;
+
+#[derive(Debug, Deserialize)]
+struct AppStateVersion {
+    version: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppState {
+    pub version: u32,
+    pub loadouts: Vec<LoadoutState>,
+    pub active_loadout: usize,
+    pub poise_data_view: PoiseDataViewState,
+    pub one_attack_plot_view: OneAttackPlotViewState,
+    /// Open state of every other window by its name
+    pub open_windows: BTreeMap<String, bool>,
+}
+
+impl AppState {
+    pub fn load(storage: &dyn eframe::Storage) -> Option<Self> {
+        let version = eframe::get_value::<AppStateVersion>(storage, APP_STATE_KEY)?.version;
+
+        if version != APP_STATE_VERSION {
+            log::warn!(
+                "Discarding saved state with version {}, expected version {}",
+                version,
+                APP_STATE_VERSION
+            );
+            return None;
+        }
+
+        eframe::get_value::<Self>(storage, APP_STATE_KEY)
+    }
+
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, APP_STATE_KEY, self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashMap};
+
+    use super::*;
+    use crate::{data::Attacks, equipment_view::EquipmentViewState};
+
+    #[derive(Default)]
+    struct MemoryStorage(HashMap<String, String>);
+
+    impl eframe::Storage for MemoryStorage {
+        fn get_string(&self, key: &str) -> Option<String> {
+            self.0.get(key).cloned()
+        }
+
+        fn set_string(&mut self, key: &str, value: String) {
+            self.0.insert(key.to_string(), value);
+        }
+
+        fn flush(&mut self) {}
+    }
+
+    fn state(version: u32) -> AppState {
+        AppState {
+            version,
+            loadouts: vec![LoadoutState {
+                name: "Claymore".to_string(),
+                equipment_view: EquipmentViewState {
+                    is_open: true,
+                    selected_weapon: Some("Claymore".to_string()),
+                    selected_attack: Some(Attacks::TwoHandedR1Chain(0)),
+                    armor_poise: 51,
+                    selected_modifiers: BTreeSet::from(["Bull-Goat's Talisman".to_string()]),
+                    is_in_recovery: false,
+                },
+            }],
+            active_loadout: 0,
+            poise_data_view: PoiseDataViewState {
+                is_open: false,
+                selected_weapon: None,
+            },
+            one_attack_plot_view: OneAttackPlotViewState {
+                is_open: true,
+                selected_attacks: vec![Attacks::Backstab(crate::data::CriticalSize::Small)],
+                selected_weapon_classes: BTreeMap::from([("Dagger".to_string(), false)]),
+                point_radius: 3.0,
+                layout: crate::one_attack_plot_view::PlotLayout::GroupedByClass,
+            },
+            open_windows: BTreeMap::from([("Heatmap".to_string(), true)]),
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut storage = MemoryStorage::default();
+
+        state(APP_STATE_VERSION).save(&mut storage);
+
+        assert_eq!(AppState::load(&storage), Some(state(APP_STATE_VERSION)));
+
+        state(APP_STATE_VERSION + 1).save(&mut storage);
+
+        assert_eq!(AppState::load(&storage), None);
+    }
+}
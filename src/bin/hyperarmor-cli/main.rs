@@ -0,0 +1,382 @@
+#[cfg(feature = "gui")]
+mod plots;
+mod repl;
+
+use std::{io::Write, path::PathBuf, process::ExitCode};
+
+use hyperarmor_inspector::{
+    api,
+    data::{load_data_from, Attacks},
+    equipment_view::EquipmentView,
+    matchup,
+    query::{self, LoadoutQuery},
+    static_data::{set_poise_data, INNATE_WEAPON_POISE, POISE_DATA, WEAPON_CLASSES},
+    table_export::Table,
+};
+
+const USAGE: &str = "\
+Usage:
+  hyperarmor-cli <command> [options]
+
+Commands:
+  help                   prints this text
+  weapon <name>          poise damage of every attack of a weapon
+  hyperarmor             hyperarmor of --weapon during --attack
+  staggers-me            every attack that staggers the loadout
+  top --attack <attack>  weapons with the most poise damage for an attack
+  classes                weapon classes with their weapon count and innate weapon poise
+  batch <file>           evaluates every matchup of a CSV file, see Batch
+  export <plot>          saves a plot, see Plots
+  repl                   interactive prompt keeping a loadout between commands, see Repl
+  serve                  local JSON HTTP API, see Serve
+
+Loadout options:
+  --loadout <code>       loadout code copied from the loadout tabs
+  --weapon <name>        your weapon, for hyperarmor, staggers-me and weapon
+  --attack <attack>      your attack, for hyperarmor, staggers-me and weapon
+  --armor-poise <number>
+  --modifier <name>      can be given several times
+  --bullgoat             Bull-Goat's Talisman
+  --recovery             in recovery from a previous attack
+
+Options:
+  --json                 prints JSON instead of a table
+  --data <file>          poise data CSV, poise_data.csv in the working directory by default
+  --max-hits <number>    hits an attack may take to stagger in staggers-me, 1 by default
+  --class <class>        only weapons of a class in staggers-me and top
+  --limit <number>       weapons listed by top, 10 by default
+
+Plots:
+  one-attack    poise damage of every weapon, --attack can be given several times
+  scatter       poise damage of --attack against --attack-b
+  distribution  histogram of the poise damage of --attack
+  coverage      share of weapons tanked by poise for --attack
+  heatmap       every weapon and attack, coloured by whether it staggers
+  Only available when built with the gui feature.
+
+Plot options:
+  --attack-b <attack>    second attack of the scatter plot
+  --layout <layout>      alphabetical, sorted, grouped or strip
+  --bin-width <number>   histogram bin width, 5 by default
+  --output <file>        .svg or .png, a new <plot>.svg by default
+
+Batch:
+  The CSV file has the columns my_weapon, my_attack, armor_poise, modifiers (separated by ;),
+  their_weapon, their_attack and optionally their_armor_poise. Both attacks hit at the same time,
+  the results are written to --output, or printed if not given.
+
+Repl:
+  Loads the poise data once and reads commands with the options above, tab completes commands,
+  options, weapons, attacks, classes and modifiers. Type help in it for the session variables.
+
+Serve:
+  --address <address>    127.0.0.1:8080 by default, the schema is at /openapi.json
+  GET  /weapons[?class=]       GET /weapons/<name>   GET /classes   GET /attacks
+  GET  /hyperarmor?<loadout>   GET /staggers-me?<loadout>&max-hits=&class=
+  POST /trade with a JSON matchup, like a row of the batch file
+  <loadout> takes the loadout options as parameters, e.g. weapon=Zweihander&bullgoat=true
+
+Attacks are written like TwoHandedR1Chain(0) or \"two handed r1 chain 0\".";
+
+/// Options without a value
+const FLAGS: [&str; 3] = ["json", "bullgoat", "recovery"];
+
+/// Positional arguments, flags and `--name value` options in the given order
+#[derive(Default)]
+struct Args {
+    positional: Vec<String>,
+    flags: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut flags = Vec::new();
+        let mut options = Vec::new();
+
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if FLAGS.contains(&name) => flags.push(name.to_string()),
+                Some(name) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("Missing value for --{}", name))?;
+
+                    options.push((name.to_string(), value));
+                }
+                None => positional.push(arg),
+            }
+        }
+
+        Ok(Self {
+            positional,
+            flags,
+            options,
+        })
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    fn values(&self, name: &str) -> Vec<&String> {
+        self.options
+            .iter()
+            .filter(|(option, _)| option == name)
+            .map(|(_, value)| value)
+            .collect()
+    }
+
+    fn value(&self, name: &str) -> Option<&String> {
+        self.values(name).last().copied()
+    }
+
+    fn number<T: std::str::FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.value(name) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("Invalid number {} for --{}", value, name)),
+            None => Ok(default),
+        }
+    }
+}
+
+fn attack(name: &str) -> Result<Attacks, String> {
+    query::find_attack(name).map_err(|error| error.to_string())
+}
+
+fn required_attack(args: &Args, option: &str) -> Result<Attacks, String> {
+    attack(
+        args.value(option)
+            .ok_or_else(|| format!("Missing --{}", option))?,
+    )
+}
+
+/// `--attack` is the plotted or ranked attack in `export` and `top`, not the loadout's
+fn equipment_view(args: &Args, is_attack_of_loadout: bool) -> Result<EquipmentView, String> {
+    let loadout_query = LoadoutQuery {
+        code: args.value("loadout").cloned(),
+        weapon: args.value("weapon").cloned(),
+        attack: match is_attack_of_loadout {
+            true => args.value("attack").cloned(),
+            false => None,
+        },
+        armor_poise: args
+            .value("armor-poise")
+            .map(|_| args.number("armor-poise", 0))
+            .transpose()?,
+        modifiers: args.values("modifier").into_iter().cloned().collect(),
+        is_bull_goat: args.flag("bullgoat"),
+        is_in_recovery: args.flag("recovery"),
+    };
+
+    loadout_query
+        .equipment_view()
+        .map_err(|error| error.to_string())
+}
+
+/// Ignores a closed stdout, e.g. when piped into `head`
+fn write_stdout(text: &str) {
+    let _ = std::io::stdout().write_all(text.as_bytes());
+}
+
+fn print(table: &Table, args: &Args) {
+    match args.flag("json") {
+        true => write_stdout(&format!("{}\n", table.to_json())),
+        false => write_stdout(&table.to_text()),
+    }
+}
+
+fn weapon(args: &Args) -> Result<(), String> {
+    let name = args.positional[1..].join(" ");
+
+    if name.is_empty() {
+        return Err("Missing weapon".to_string());
+    }
+
+    let table = query::weapon_table(&name, &equipment_view(args, true)?)
+        .map_err(|error| error.to_string())?;
+
+    print(&table, args);
+
+    Ok(())
+}
+
+/// A single row, printed as one line per column
+fn hyperarmor(args: &Args) -> Result<(), String> {
+    let table =
+        query::hyperarmor_table(&equipment_view(args, true)?).map_err(|error| error.to_string())?;
+
+    if args.flag("json") {
+        print(&table, args);
+        return Ok(());
+    }
+
+    let width = table
+        .columns
+        .iter()
+        .map(|column| column.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    let text = table
+        .columns
+        .iter()
+        .zip(&table.rows[0])
+        .chain(table.metadata.iter().map(|(name, value)| (name, value)))
+        .map(|(name, value)| format!("{:width$}  {}\n", name, value, width = width))
+        .collect::<String>();
+
+    write_stdout(&text);
+
+    Ok(())
+}
+
+fn staggers_me(args: &Args) -> Result<(), String> {
+    let table = query::staggers_me_report(
+        &equipment_view(args, true)?,
+        args.number("max-hits", 1)?,
+        args.value("class")
+            .map(|weapon_class| weapon_class.as_str()),
+    )
+    .map_err(|error| error.to_string())?;
+
+    print(&table, args);
+
+    Ok(())
+}
+
+fn top(args: &Args) -> Result<(), String> {
+    let table = query::top_table(
+        &required_attack(args, "attack")?,
+        &equipment_view(args, false)?,
+        args.number("limit", 10)?,
+        args.value("class")
+            .map(|weapon_class| weapon_class.as_str()),
+    )
+    .map_err(|error| error.to_string())?;
+
+    print(&table, args);
+
+    Ok(())
+}
+
+fn classes(args: &Args) -> Result<(), String> {
+    print(
+        &query::classes_table(&WEAPON_CLASSES, &INNATE_WEAPON_POISE),
+        args,
+    );
+
+    Ok(())
+}
+
+fn batch(args: &Args) -> Result<(), String> {
+    let input = args
+        .positional
+        .get(1)
+        .ok_or_else(|| "Missing matchup file".to_string())?;
+
+    let reader = std::fs::File::open(input).map_err(|error| format!("{}: {}", input, error))?;
+
+    let writer: Box<dyn Write> = match args.value("output") {
+        Some(output) => Box::new(
+            std::fs::File::create(output).map_err(|error| format!("{}: {}", output, error))?,
+        ),
+        None => Box::new(std::io::stdout()),
+    };
+
+    let (rows, failed) = matchup::evaluate_csv(reader, writer, |matchup| {
+        matchup::evaluate(&POISE_DATA, matchup)
+    })
+    .map_err(|error| format!("{}: {}", input, error))?;
+
+    if let Some(output) = args.value("output") {
+        eprintln!(
+            "Evaluated {} matchups into {}, {} failed",
+            rows, output, failed
+        );
+    }
+
+    Ok(())
+}
+
+fn serve(args: &Args) -> Result<(), String> {
+    let address = args
+        .value("address")
+        .map(|address| address.as_str())
+        .unwrap_or("127.0.0.1:8080");
+
+    let server =
+        tiny_http::Server::http(address).map_err(|error| format!("{}: {}", address, error))?;
+
+    // answers right away instead of loading the poise data on the first request
+    eprintln!(
+        "Loaded {} weapons, listening on http://{}",
+        POISE_DATA.len(),
+        server.server_addr()
+    );
+
+    api::serve(&server);
+
+    Ok(())
+}
+
+/// Runs a command, shared by the command line and the REPL
+fn run(args: &Args) -> Result<(), String> {
+    match args.positional.first().map(|command| command.as_str()) {
+        Some("weapon") => weapon(args),
+        Some("hyperarmor") => hyperarmor(args),
+        Some("staggers-me") => staggers_me(args),
+        Some("top") => top(args),
+        Some("classes") => classes(args),
+        Some("batch") => batch(args),
+        #[cfg(feature = "gui")]
+        Some("export") => plots::export(args),
+        #[cfg(not(feature = "gui"))]
+        Some("export") => Err("Plots are only exported with the gui feature".to_string()),
+        Some("help") | None => {
+            write_stdout(&format!("{}\n", USAGE));
+            Ok(())
+        }
+        Some(command) => Err(format!("Unknown command {}", command)),
+    }
+}
+
+fn main() -> ExitCode {
+    simple_logger::SimpleLogger::new()
+        .with_level(log::LevelFilter::Warn)
+        .init()
+        .unwrap();
+
+    let result = Args::parse(std::env::args().skip(1)).and_then(|args| {
+        if let Some(path) = args.value("data") {
+            let path = PathBuf::from(path);
+
+            if !path.exists() {
+                return Err(format!("{} does not exist", path.display()));
+            }
+
+            let data = load_data_from(&path)
+                .map_err(|error| format!("Could not load {}: {}", path.display(), error))?;
+
+            set_poise_data(data).map_err(|_| "Poise data already loaded".to_string())?;
+        }
+
+        match args.positional.first().map(|command| command.as_str()) {
+            Some("repl") => repl::run(),
+            Some("serve") => serve(&args),
+            _ => run(&args),
+        }
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}\nRun hyperarmor-cli help for the usage", error);
+            ExitCode::FAILURE
+        }
+    }
+}
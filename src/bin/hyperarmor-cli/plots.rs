@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+
+use hyperarmor_inspector::{
+    coverage_view::{coverage_curve_chart, histogram_chart},
+    data::Attacks,
+    equipment_view::effective_poise,
+    export_folder::unused_path,
+    heatmap_view::HeatmapView,
+    one_attack_plot_view::{one_attack_chart, plot_series, PlotLayout},
+    plot_export::{self, Chart},
+    query::weapon_points_for_attack,
+    static_data::{POISE_DATA, WEAPON_CLASSES},
+    two_attack_scatter_view::{scatter_chart, scatter_points},
+};
+
+use crate::{attack, equipment_view, required_attack, write_stdout, Args};
+
+fn layout(args: &Args) -> Result<PlotLayout, String> {
+    match args.value("layout").map(|layout| layout.as_str()) {
+        None | Some("alphabetical") => Ok(PlotLayout::Alphabetical),
+        Some("sorted") => Ok(PlotLayout::SortedByPoiseDamage),
+        Some("grouped") => Ok(PlotLayout::GroupedByClass),
+        Some("strip") => Ok(PlotLayout::Strip),
+        Some(layout) => Err(format!("Unknown layout {}", layout)),
+    }
+}
+
+fn chart(plot: &str, args: &Args) -> Result<Chart, String> {
+    let equipment_view = equipment_view(args, false)?;
+
+    let multiplier = equipment_view
+        .incoming_poise_damage_multiplier
+        .unwrap_or(1.0);
+    let hyperarmor = equipment_view.hyperarmor.unwrap_or_default();
+    let armor_poise = equipment_view.armor_poise as f64;
+
+    let poise_damage_values =
+        |attack: &Attacks| POISE_DATA.get_poise_damage_values_for_attack(attack, multiplier);
+
+    match plot {
+        "one-attack" => {
+            let attacks = args
+                .values("attack")
+                .into_iter()
+                .map(|name| attack(name))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if attacks.is_empty() {
+                return Err("Missing --attack".to_string());
+            }
+
+            let weapon_points_by_attack = attacks
+                .iter()
+                .map(|attack| {
+                    weapon_points_for_attack(
+                        &POISE_DATA,
+                        attack,
+                        &equipment_view.incoming_poise_damage_multiplier,
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let all_weapon_classes = WEAPON_CLASSES
+                .keys()
+                .map(|weapon_class| (weapon_class.clone(), true))
+                .collect();
+
+            let layout = layout(args)?;
+
+            Ok(one_attack_chart(
+                &plot_series(&weapon_points_by_attack, &all_weapon_classes, layout),
+                &attacks,
+                layout,
+                3.0,
+                &equipment_view,
+                &[],
+            ))
+        }
+        "scatter" => {
+            let attack_a = required_attack(args, "attack")?;
+            let attack_b = required_attack(args, "attack-b")?;
+
+            Ok(scatter_chart(
+                &scatter_points(&POISE_DATA, &attack_a, &attack_b, multiplier),
+                &attack_a,
+                &attack_b,
+                3.0,
+                hyperarmor,
+                armor_poise,
+            ))
+        }
+        "distribution" => {
+            let attack = required_attack(args, "attack")?;
+
+            let bin_width: f64 = args.number("bin-width", 5.0)?;
+
+            if !bin_width.is_finite() || bin_width <= 0.0 {
+                return Err(format!("Invalid bin width {}", bin_width));
+            }
+
+            Ok(histogram_chart(
+                &poise_damage_values(&attack),
+                bin_width,
+                &attack,
+                hyperarmor,
+                armor_poise,
+            ))
+        }
+        "coverage" => {
+            let attack = required_attack(args, "attack")?;
+
+            Ok(coverage_curve_chart(
+                &poise_damage_values(&attack),
+                &attack,
+                hyperarmor,
+                armor_poise,
+            ))
+        }
+        "heatmap" => Ok(HeatmapView::new().chart(
+            multiplier,
+            effective_poise(hyperarmor, equipment_view.armor_poise),
+        )),
+        plot => Err(format!("Unknown plot {}", plot)),
+    }
+}
+
+pub fn export(args: &Args) -> Result<(), String> {
+    let plot = args
+        .positional
+        .get(1)
+        .ok_or_else(|| "Missing plot".to_string())?;
+
+    let chart = chart(plot, args)?;
+
+    // only an explicit --output is overwritten
+    let output = args
+        .value("output")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| unused_path(Path::new(""), plot, "svg"));
+
+    plot_export::save(&chart, &output).map_err(|error| error.to_string())?;
+
+    write_stdout(&format!("Saved {}\n", output.display()));
+
+    Ok(())
+}
@@ -0,0 +1,410 @@
+use hyperarmor_inspector::{
+    data::Attacks,
+    static_data::{MODIFIERS, WEAPONS, WEAPON_CLASSES},
+    table_export::loadout_metadata,
+};
+use rustyline::{
+    completion::{Completer, Pair},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    history::DefaultHistory,
+    validate::Validator,
+    CompletionType, Config, Context, Editor, Helper,
+};
+
+use crate::{equipment_view, write_stdout, Args, FLAGS, USAGE};
+
+const REPL_USAGE: &str = "\
+Session:
+  set <variable> [value]  sets a variable added to every command, modifier adds one more
+  unset <variable>        clears a variable, every modifier for modifier
+  show                    prints the variables and the loadout they make
+  help                    prints this text and the command line usage
+  exit                    leaves the REPL, like Ctrl-D
+
+Variables:
+  loadout, weapon, attack, armor-poise, modifier, bullgoat, recovery and json
+
+Commands take the same options as on the command line, they win over the variables for that
+line. Values with spaces are written in double quotes, except after set.";
+
+/// Commands of the command line besides `repl`
+const COMMANDS: [&str; 8] = [
+    "weapon",
+    "hyperarmor",
+    "staggers-me",
+    "top",
+    "classes",
+    "batch",
+    "export",
+    "help",
+];
+
+const SESSION_COMMANDS: [&str; 5] = ["set", "unset", "show", "exit", "quit"];
+
+/// Options `set` keeps, `modifier` can be set several times
+const VARIABLES: [&str; 5] = ["loadout", "weapon", "attack", "armor-poise", "modifier"];
+
+/// Options with a value, `--data` aside since the poise data is already loaded
+const OPTIONS: [&str; 12] = [
+    "loadout",
+    "weapon",
+    "attack",
+    "armor-poise",
+    "modifier",
+    "max-hits",
+    "class",
+    "limit",
+    "attack-b",
+    "layout",
+    "bin-width",
+    "output",
+];
+
+const PLOTS: [&str; 5] = [
+    "one-attack",
+    "scatter",
+    "distribution",
+    "coverage",
+    "heatmap",
+];
+
+const LAYOUTS: [&str; 4] = ["alphabetical", "sorted", "grouped", "strip"];
+
+/// Words of `line` with their byte offsets, double quotes keep spaces in a word
+///
+/// Also returns whether the last quote is still open.
+fn words(line: &str) -> (Vec<(usize, String)>, bool) {
+    let mut words = Vec::new();
+    let mut word: Option<(usize, String)> = None;
+    let mut is_quoted = false;
+
+    for (offset, character) in line.char_indices() {
+        match character {
+            '"' => {
+                is_quoted = !is_quoted;
+                word.get_or_insert_with(|| (offset, String::new()));
+            }
+            character if character.is_whitespace() && !is_quoted => words.extend(word.take()),
+            character => word
+                .get_or_insert_with(|| (offset, String::new()))
+                .1
+                .push(character),
+        }
+    }
+
+    words.extend(word);
+
+    (words, is_quoted)
+}
+
+fn split_line(line: &str) -> Result<Vec<String>, String> {
+    match words(line) {
+        (_, true) => Err("Unclosed quote".to_string()),
+        (words, false) => Ok(words.into_iter().map(|(_, word)| word).collect()),
+    }
+}
+
+/// Variables added to every command of the session
+#[derive(Debug, Clone, Default)]
+struct Session {
+    options: Vec<(String, String)>,
+    flags: Vec<String>,
+}
+
+impl Session {
+    /// The options of the line come last, so they win over the variables
+    fn args(&self, line: Args) -> Args {
+        Args {
+            positional: line.positional,
+            flags: self.flags.iter().cloned().chain(line.flags).collect(),
+            options: self.options.iter().cloned().chain(line.options).collect(),
+        }
+    }
+
+    /// Keeps the session as it was if the loadout can't be built with `value`
+    fn set(&mut self, variable: &str, value: Option<String>) -> Result<(), String> {
+        let mut session = self.clone();
+
+        match value {
+            None if FLAGS.contains(&variable) => {
+                session.flags.retain(|flag| flag != variable);
+                session.flags.push(variable.to_string());
+            }
+            Some(_) if FLAGS.contains(&variable) => {
+                return Err(format!("{} takes no value", variable))
+            }
+            Some(value) if VARIABLES.contains(&variable) => {
+                if variable != "modifier" {
+                    session.options.retain(|(option, _)| option != variable);
+                }
+
+                session.options.push((variable.to_string(), value));
+            }
+            None if VARIABLES.contains(&variable) => {
+                return Err(format!("Missing value for {}", variable))
+            }
+            _ => return Err(format!("Unknown variable {}", variable)),
+        }
+
+        equipment_view(&session.args(Args::default()), true)?;
+
+        *self = session;
+
+        Ok(())
+    }
+
+    fn unset(&mut self, variable: &str) -> Result<(), String> {
+        if !FLAGS.contains(&variable) && !VARIABLES.contains(&variable) {
+            return Err(format!("Unknown variable {}", variable));
+        }
+
+        self.options.retain(|(option, _)| option != variable);
+        self.flags.retain(|flag| flag != variable);
+
+        Ok(())
+    }
+
+    fn show(&self) -> Result<(), String> {
+        let equipment_view = equipment_view(&self.args(Args::default()), true)?;
+
+        let mut text = self
+            .options
+            .iter()
+            .map(|(option, value)| format!("{} = {}\n", option, value))
+            .chain(self.flags.iter().map(|flag| format!("{}\n", flag)))
+            .collect::<String>();
+
+        if !text.is_empty() {
+            text.push('\n');
+        }
+
+        for (name, value) in loadout_metadata(&equipment_view) {
+            text.push_str(&format!("{}: {}\n", name, value));
+        }
+
+        write_stdout(&text);
+
+        Ok(())
+    }
+}
+
+/// Runs a line of the REPL, returns false to leave it
+fn run_line(session: &mut Session, line: &str) -> Result<bool, String> {
+    let words = split_line(line)?;
+
+    let variable = || words.get(1).ok_or_else(|| "Missing variable".to_string());
+
+    match words.first().map(|command| command.as_str()) {
+        None => {}
+        Some("exit" | "quit") => return Ok(false),
+        Some("help") => write_stdout(&format!("{}\n\n{}\n", REPL_USAGE, USAGE)),
+        Some("set") => {
+            let value = words[2.min(words.len())..].join(" ");
+
+            session.set(variable()?, (!value.is_empty()).then_some(value))?;
+        }
+        Some("unset") => session.unset(variable()?)?,
+        Some("show") => session.show()?,
+        Some("repl") => return Err("Already in the REPL".to_string()),
+        Some(_) => {
+            let args = session.args(Args::parse(words)?);
+
+            if args.value("data").is_some() {
+                return Err(
+                    "Poise data already loaded, give --data to hyperarmor-cli repl".to_string(),
+                );
+            }
+
+            crate::run(&args)?;
+        }
+    }
+
+    Ok(true)
+}
+
+/// Lowercase letters and digits, so `two handed r1` completes to `TwoHandedR1Chain(0)`
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_lowercase()
+}
+
+fn options() -> Vec<String> {
+    OPTIONS
+        .iter()
+        .chain(&FLAGS)
+        .map(|option| format!("--{}", option))
+        .collect()
+}
+
+/// What can follow the `previous` words of the line
+fn candidates(previous: &[String]) -> Vec<String> {
+    let previous = previous
+        .iter()
+        .map(|word| word.as_str())
+        .collect::<Vec<_>>();
+
+    let expected = match previous.as_slice() {
+        [] => "command",
+        ["set" | "unset"] => "variable",
+        [.., option] if option.starts_with("--") && !FLAGS.contains(&&option[2..]) => &option[2..],
+        ["set", variable] => variable,
+        ["weapon"] => "weapon",
+        ["export"] => "plot",
+        ["set" | "unset", ..] => "",
+        _ => "option",
+    };
+
+    let strings = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+
+    match expected {
+        "command" => strings(&[&COMMANDS[..], &SESSION_COMMANDS[..]].concat()),
+        "variable" => strings(&[&VARIABLES[..], &FLAGS[..]].concat()),
+        "weapon" => WEAPONS.clone(),
+        "attack" | "attack-b" => Attacks::all()
+            .iter()
+            .map(|attack| format!("{:?}", attack))
+            .collect(),
+        "class" => WEAPON_CLASSES.keys().cloned().collect(),
+        "modifier" => MODIFIERS
+            .iter()
+            .map(|modifier| modifier.name.clone())
+            .collect(),
+        "plot" => strings(&PLOTS),
+        "layout" => strings(&LAYOUTS),
+        "option" => options(),
+        _ => Vec::new(),
+    }
+}
+
+/// Completes the word under the cursor from the words before it
+struct ReplHelper;
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _context: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let (mut words, is_quoted) = words(line);
+
+        let (start, word) = match line.ends_with(char::is_whitespace) && !is_quoted {
+            true => (pos, String::new()),
+            false => words.pop().unwrap_or((pos, String::new())),
+        };
+
+        let previous = words.into_iter().map(|(_, word)| word).collect::<Vec<_>>();
+
+        let candidates = match word.starts_with("--") {
+            true => options(),
+            false => candidates(&previous),
+        };
+
+        let pairs = candidates
+            .into_iter()
+            .filter(|candidate| normalize(candidate).starts_with(&normalize(&word)))
+            .map(|candidate| Pair {
+                replacement: match candidate.contains(char::is_whitespace) {
+                    true => format!("\"{}\"", candidate),
+                    false => candidate.clone(),
+                },
+                display: candidate,
+            })
+            .collect();
+
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+pub fn run() -> Result<(), String> {
+    let config = Config::builder()
+        .completion_type(CompletionType::List)
+        .auto_add_history(true)
+        .build();
+
+    let mut editor = Editor::<ReplHelper, DefaultHistory>::with_config(config)
+        .map_err(|error| error.to_string())?;
+    editor.set_helper(Some(ReplHelper));
+
+    write_stdout(&format!(
+        "Loaded {} weapons, type help for the commands\n",
+        WEAPONS.len()
+    ));
+
+    let mut session = Session::default();
+
+    loop {
+        match editor.readline("> ") {
+            Ok(line) => match run_line(&mut session, &line) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(error) => eprintln!("{}", error),
+            },
+            // Ctrl-C only drops the line being typed
+            Err(ReadlineError::Interrupted) => {}
+            Err(ReadlineError::Eof) => break,
+            Err(error) => return Err(error.to_string()),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_line() {
+        assert_eq!(
+            split_line(r#"top --attack "two handed r1 chain 0"  --limit 3"#).unwrap(),
+            vec!["top", "--attack", "two handed r1 chain 0", "--limit", "3"]
+        );
+        assert!(split_line(r#"weapon "Great Stars"#).is_err());
+        assert_eq!(
+            words(r#"set weapon "Great St"#).0[2],
+            (11, "Great St".to_string())
+        );
+    }
+
+    #[test]
+    fn test_session_args() {
+        let session = Session {
+            options: vec![("armor-poise".to_string(), "51".to_string())],
+            flags: vec!["bullgoat".to_string()],
+        };
+
+        let args =
+            session.args(Args::parse(split_line("hyperarmor --armor-poise 60").unwrap()).unwrap());
+
+        assert_eq!(args.value("armor-poise").unwrap(), "60");
+        assert!(args.flag("bullgoat"));
+    }
+
+    #[test]
+    fn test_candidates() {
+        let candidates = |line: &str| candidates(&split_line(line).unwrap());
+
+        assert!(candidates("").contains(&"staggers-me".to_string()));
+        assert!(candidates("top --attack").contains(&"TwoHandedR1Chain(0)".to_string()));
+        assert!(candidates("top --json").contains(&"--limit".to_string()));
+        assert_eq!(candidates("set bullgoat"), Vec::<String>::new());
+    }
+}
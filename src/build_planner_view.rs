@@ -0,0 +1,472 @@
+use std::fmt::{Display, Formatter};
+
+use egui::{DragValue, Layout, Slider};
+use egui_extras::{Column, TableBuilder};
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::{
+    data::Attacks,
+    equipment_view::{hyperarmor_multiplier, EquipmentView},
+    selection::{SelectionBus, SelectionEvent},
+    static_data::{MODIFIERS, POISE_DATA},
+    table_export::{Table, TableExportControls},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumIter)]
+pub enum RollTier {
+    Light,
+    Medium,
+    Heavy,
+}
+
+impl RollTier {
+    /// Equip load ratio the tier has to stay below
+    pub fn max_equip_load_ratio(&self) -> f64 {
+        match self {
+            RollTier::Light => 0.3,
+            RollTier::Medium => 0.7,
+            RollTier::Heavy => 1.0,
+        }
+    }
+
+    pub fn from_equip_load(equip_load: f64, max_equip_load: f64) -> Option<Self> {
+        RollTier::iter().find(|tier| equip_load < max_equip_load * tier.max_equip_load_ratio())
+    }
+}
+
+impl Display for RollTier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} Roll", self)
+    }
+}
+
+// (endurance, max equip load, exponent) of the endurance to equip load curve
+const EQUIP_LOAD_CURVE: [(f64, f64, f64); 4] = [
+    (8.0, 45.0, 1.1),
+    (25.0, 72.0, 1.2),
+    (60.0, 120.0, 1.0),
+    (99.0, 160.0, 1.0),
+];
+
+pub fn max_equip_load(endurance: u8) -> f64 {
+    let endurance = (endurance as f64).clamp(EQUIP_LOAD_CURVE[0].0, EQUIP_LOAD_CURVE[3].0);
+
+    for stages in EQUIP_LOAD_CURVE.windows(2) {
+        let (start_endurance, start_load, exponent) = stages[0];
+        let (end_endurance, end_load, _) = stages[1];
+
+        if endurance > end_endurance {
+            continue;
+        }
+
+        let ratio = (endurance - start_endurance) / (end_endurance - start_endurance);
+
+        // the curve grows slowly at first, then flattens towards the end
+        let ratio = match start_endurance < 25.0 {
+            true => ratio.powf(exponent),
+            false => 1.0 - (1.0 - ratio).powf(exponent),
+        };
+
+        return start_load + (end_load - start_load) * ratio;
+    }
+
+    EQUIP_LOAD_CURVE[3].1
+}
+
+/// Smallest armor poise that tanks `incoming_poise_damage` during an attack with `weapon_hyperarmor`
+///
+/// `hyperarmor_multiplier` (recovery, hyperarmor buffs) only applies while the attack has hyperarmor
+pub fn minimum_armor_poise(
+    incoming_poise_damage: f64,
+    weapon_hyperarmor: f64,
+    hyperarmor_multiplier: f64,
+) -> u16 {
+    let hyperarmor_multiplier = match weapon_hyperarmor > 0.0 {
+        true => hyperarmor_multiplier,
+        false => 1.0,
+    };
+
+    let required_poise = incoming_poise_damage / hyperarmor_multiplier - weapon_hyperarmor;
+
+    match required_poise < 0.0 {
+        true => 0,
+        false => required_poise.floor() as u16 + 1,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoiseBreakpoint {
+    pub armor_poise: u16,
+    pub weapons_tanked: usize,
+}
+
+/// Every armor poise value at which one more weapon's attack gets tanked
+pub fn minimum_poise_breakpoints(
+    incoming_poise_damage_values: &[f64],
+    weapon_hyperarmor: f64,
+    hyperarmor_multiplier: f64,
+) -> Vec<PoiseBreakpoint> {
+    let mut required_armor_poise = incoming_poise_damage_values
+        .iter()
+        .map(|value| minimum_armor_poise(*value, weapon_hyperarmor, hyperarmor_multiplier))
+        .collect::<Vec<u16>>();
+
+    required_armor_poise.sort();
+
+    let mut breakpoints = Vec::<PoiseBreakpoint>::new();
+
+    for (i, armor_poise) in required_armor_poise.iter().enumerate() {
+        match breakpoints.last_mut() {
+            Some(breakpoint) if breakpoint.armor_poise == *armor_poise => {
+                breakpoint.weapons_tanked = i + 1;
+            }
+            _ => breakpoints.push(PoiseBreakpoint {
+                armor_poise: *armor_poise,
+                weapons_tanked: i + 1,
+            }),
+        }
+    }
+
+    breakpoints
+}
+
+pub struct BuildPlanner {
+    pub endurance: u8,
+    pub weapons_weight: f64,
+    pub talismans_weight: f64,
+    pub armor_weight: f64,
+}
+
+impl BuildPlanner {
+    pub fn max_equip_load(&self) -> f64 {
+        max_equip_load(self.endurance)
+    }
+
+    pub fn equip_load(&self) -> f64 {
+        self.weapons_weight + self.talismans_weight + self.armor_weight
+    }
+
+    /// Armor weight needed for `armor_poise`, assuming the poise per weight of the current armor
+    pub fn estimated_armor_weight(
+        &self,
+        armor_poise: u16,
+        current_armor_poise: u16,
+    ) -> Option<f64> {
+        if armor_poise == 0 {
+            return Some(0.0);
+        }
+
+        if current_armor_poise == 0 || self.armor_weight <= 0.0 {
+            return None;
+        }
+
+        Some(armor_poise as f64 * self.armor_weight / current_armor_poise as f64)
+    }
+
+    pub fn is_reachable(
+        &self,
+        armor_poise: u16,
+        current_armor_poise: u16,
+        roll_tier: &RollTier,
+    ) -> bool {
+        match self.estimated_armor_weight(armor_poise, current_armor_poise) {
+            Some(armor_weight) => {
+                self.weapons_weight + self.talismans_weight + armor_weight
+                    < self.max_equip_load() * roll_tier.max_equip_load_ratio()
+            }
+            None => false,
+        }
+    }
+}
+
+fn breakpoints_table(
+    planner: &BuildPlanner,
+    breakpoints: &[PoiseBreakpoint],
+    total_weapons: usize,
+    current_armor_poise: u16,
+) -> Table {
+    let roll_tiers = RollTier::iter()
+        .map(|tier| tier.to_string())
+        .collect::<Vec<_>>();
+
+    let columns = ["Armor Poise", "Weapons Tanked"]
+        .into_iter()
+        .chain(roll_tiers.iter().map(|tier| tier.as_str()))
+        .collect::<Vec<_>>();
+
+    let mut table = Table::new("Build Planner", &columns)
+        .with_metadata("Endurance", planner.endurance)
+        .with_metadata("Weapons Weight", planner.weapons_weight)
+        .with_metadata("Talismans Weight", planner.talismans_weight)
+        .with_metadata("Armor Weight", planner.armor_weight);
+
+    for breakpoint in breakpoints {
+        let mut row = vec![
+            breakpoint.armor_poise.to_string(),
+            format!("{} / {}", breakpoint.weapons_tanked, total_weapons),
+        ];
+
+        row.extend(RollTier::iter().map(|tier| {
+            match planner.is_reachable(breakpoint.armor_poise, current_armor_poise, &tier) {
+                true => "✔".to_string(),
+                false => "✘".to_string(),
+            }
+        }));
+
+        table.push_row(row);
+    }
+
+    table
+}
+
+pub struct BuildPlannerView {
+    pub is_open: bool,
+    planner: BuildPlanner,
+    selected_attack: Option<Attacks>,
+    is_attack_changed: bool,
+    incoming_poise_damage_values: Vec<f64>,
+    table_export_controls: TableExportControls,
+}
+
+impl Default for BuildPlannerView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BuildPlannerView {
+    pub fn new() -> Self {
+        Self {
+            is_open: false,
+            planner: BuildPlanner {
+                endurance: 25,
+                weapons_weight: 0.0,
+                talismans_weight: 0.0,
+                armor_weight: 0.0,
+            },
+            selected_attack: Some(Attacks::TwoHandedR1Chain(0)),
+            is_attack_changed: true,
+            incoming_poise_damage_values: Vec::new(),
+            table_export_controls: TableExportControls::default(),
+        }
+    }
+
+    pub fn on_selection_event(&mut self, event: &SelectionEvent) {
+        if let SelectionEvent::AttackSelected(attack) = event {
+            if self.selected_attack.as_ref() != Some(attack) {
+                self.selected_attack = Some(attack.clone());
+                self.is_attack_changed = true;
+            }
+        }
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        equipment_view: &EquipmentView,
+        selection_bus: &mut SelectionBus,
+    ) {
+        let EquipmentView {
+            is_changed_incoming_poise_damage_multiplier,
+            incoming_poise_damage_multiplier,
+            weapon_hyperarmor,
+            armor_poise,
+            ..
+        } = equipment_view;
+
+        let hyperarmor_multiplier = hyperarmor_multiplier(
+            equipment_view.is_in_recovery,
+            &equipment_view.selected_modifiers,
+            &MODIFIERS,
+        );
+        egui::Window::new("Build Planner")
+            .id("Build Planner Window".into())
+            .resizable(true)
+            .title_bar(true)
+            .open(&mut self.is_open)
+            .show(ui.ctx(), |ui| {
+                if let Some(attack) = &self.selected_attack {
+                    if self.is_attack_changed || *is_changed_incoming_poise_damage_multiplier {
+                        self.incoming_poise_damage_values = POISE_DATA
+                            .get_poise_damage_values_for_attack_by_class(
+                                attack,
+                                incoming_poise_damage_multiplier,
+                            )
+                            .into_values()
+                            .flatten()
+                            .map(|(_weapon, poise_damage)| poise_damage)
+                            .collect();
+                    }
+                }
+
+                ui.add(Slider::new(&mut self.planner.endurance, 8..=99).text("Endurance"));
+
+                ui.horizontal(|ui| {
+                    ui.label("Weapons Weight: ");
+                    ui.add(DragValue::new(&mut self.planner.weapons_weight).speed(0.1));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Talismans Weight: ");
+                    ui.add(DragValue::new(&mut self.planner.talismans_weight).speed(0.1));
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Armor Weight: ");
+                    ui.add(DragValue::new(&mut self.planner.armor_weight).speed(0.1));
+                });
+
+                let max_equip_load = self.planner.max_equip_load();
+                let equip_load = self.planner.equip_load();
+
+                ui.label(format!(
+                    "Equip Load: {:.1} / {:.1} ({})",
+                    equip_load,
+                    max_equip_load,
+                    RollTier::from_equip_load(equip_load, max_equip_load)
+                        .map(|tier| tier.to_string())
+                        .unwrap_or("Overloaded".to_string())
+                ));
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.label("Incoming Attack: ");
+
+                    self.is_attack_changed = Attacks::combobox(ui, &mut self.selected_attack);
+
+                    if let (true, Some(attack)) = (self.is_attack_changed, &self.selected_attack) {
+                        selection_bus.publish(SelectionEvent::AttackSelected(attack.clone()));
+                    }
+                });
+
+                let breakpoints = minimum_poise_breakpoints(
+                    &self.incoming_poise_damage_values,
+                    weapon_hyperarmor.unwrap_or_default(),
+                    hyperarmor_multiplier,
+                );
+
+                let total_weapons = self.incoming_poise_damage_values.len();
+
+                if let Some(export) = self.table_export_controls.show(ui) {
+                    let mut table =
+                        breakpoints_table(&self.planner, &breakpoints, total_weapons, *armor_poise);
+
+                    if let Some(attack) = &self.selected_attack {
+                        table = table.with_metadata("Incoming Attack", attack);
+                    }
+
+                    let table = table.with_loadout(equipment_view);
+
+                    self.table_export_controls
+                        .export(ui, export, "build_planner", &table);
+                }
+
+                TableBuilder::new(ui)
+                    .column(Column::auto().resizable(true)) // Armor Poise
+                    .column(Column::auto().resizable(true)) // Weapons Tanked
+                    .columns(Column::auto(), RollTier::iter().count())
+                    .cell_layout(Layout::centered_and_justified(egui::Direction::TopDown))
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            ui.label("Armor Poise");
+                        });
+                        header.col(|ui| {
+                            ui.label("Weapons Tanked");
+                        });
+                        for tier in RollTier::iter() {
+                            header.col(|ui| {
+                                ui.label(tier.to_string());
+                            });
+                        }
+                    })
+                    .body(|body| {
+                        body.rows(20.0, breakpoints.len(), |mut row| {
+                            let breakpoint = &breakpoints[row.index()];
+
+                            row.col(|ui| {
+                                ui.label(breakpoint.armor_poise.to_string());
+                            });
+
+                            row.col(|ui| {
+                                ui.label(format!(
+                                    "{} / {}",
+                                    breakpoint.weapons_tanked, total_weapons
+                                ));
+                            });
+
+                            for tier in RollTier::iter() {
+                                row.col(|ui| {
+                                    match self.planner.is_reachable(
+                                        breakpoint.armor_poise,
+                                        *armor_poise,
+                                        &tier,
+                                    ) {
+                                        true => ui.label("✔"),
+                                        false => ui.label("✘"),
+                                    };
+                                });
+                            }
+                        });
+                    });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_equip_load() {
+        assert_eq!(max_equip_load(8), 45.0);
+        assert_eq!(max_equip_load(25), 72.0);
+        assert_eq!(max_equip_load(60), 120.0);
+        assert_eq!(max_equip_load(99), 160.0);
+        assert_eq!(max_equip_load(1), 45.0);
+        assert!(max_equip_load(40) > 72.0 && max_equip_load(40) < 120.0);
+    }
+
+    #[test]
+    fn test_minimum_poise_breakpoints() {
+        assert_eq!(minimum_armor_poise(50.0, 0.0, 0.8), 51);
+        assert_eq!(minimum_armor_poise(50.0, 59.0, 1.0), 0);
+        assert_eq!(minimum_armor_poise(80.0, 44.25, 0.8), 56);
+
+        let breakpoints = minimum_poise_breakpoints(&[30.0, 50.0, 50.0, 10.0], 20.0, 1.0);
+
+        assert_eq!(
+            breakpoints,
+            vec![
+                PoiseBreakpoint {
+                    armor_poise: 0,
+                    weapons_tanked: 1
+                },
+                PoiseBreakpoint {
+                    armor_poise: 11,
+                    weapons_tanked: 2
+                },
+                PoiseBreakpoint {
+                    armor_poise: 31,
+                    weapons_tanked: 4
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_reachable() {
+        let planner = BuildPlanner {
+            endurance: 25,
+            weapons_weight: 20.0,
+            talismans_weight: 2.0,
+            armor_weight: 20.0,
+        };
+
+        assert!(planner.is_reachable(40, 40, &RollTier::Medium));
+        assert!(!planner.is_reachable(40, 40, &RollTier::Light));
+        assert!(!planner.is_reachable(90, 40, &RollTier::Medium));
+        assert!(planner.is_reachable(90, 40, &RollTier::Heavy));
+        assert!(!planner.is_reachable(10, 0, &RollTier::Heavy));
+    }
+}
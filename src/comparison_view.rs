@@ -0,0 +1,276 @@
+use std::collections::BTreeSet;
+
+use egui::{Layout, RichText};
+use egui_extras::{Column, TableBuilder};
+
+use crate::{
+    data::Attacks,
+    equipment_view::{
+        hyperarmor_and_incoming_poise_damage_multiplier, weapon_hyperarmor_from_weapon_and_attack,
+        EquipmentView,
+    },
+    modifiers::ModifierTarget,
+    static_data::{INNATE_WEAPON_POISE, MODIFIERS, POISE_DATA},
+    table_export::{Table, TableExportControls},
+    weapon_select_view::WeaponSelectView,
+};
+
+/// Indices of the highest and lowest value, if there are at least two different values
+pub fn best_and_worst(values: &[Option<f64>]) -> Option<(usize, usize)> {
+    let values = values
+        .iter()
+        .enumerate()
+        .filter_map(|(i, value)| value.map(|value| (i, value)))
+        .collect::<Vec<_>>();
+
+    let best = values.iter().max_by(|a, b| a.1.total_cmp(&b.1))?;
+    let worst = values.iter().min_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    match best.1 > worst.1 {
+        true => Some((best.0, worst.0)),
+        false => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ComparisonCell {
+    poise_damage: f64,
+    hyperarmor: f64,
+}
+
+fn comparison_cell(
+    weapon: &str,
+    attack: &Attacks,
+    armor_poise: u16,
+    is_in_recovery: bool,
+    selected_modifiers: &BTreeSet<String>,
+) -> Option<ComparisonCell> {
+    let weapon_poise_damage = POISE_DATA.get(weapon)?;
+
+    let poise_damage_values = weapon_poise_damage.get_poise_damage_by_attack(attack)?;
+
+    if poise_damage_values.is_empty() {
+        return None;
+    }
+
+    let weapon_hyperarmor = weapon_hyperarmor_from_weapon_and_attack(
+        *INNATE_WEAPON_POISE.get(weapon)?,
+        attack.get_hyper_armour_multiplier(),
+        &weapon_poise_damage.class,
+        &weapon.to_string(),
+        attack,
+    );
+
+    let (hyperarmor, _) = hyperarmor_and_incoming_poise_damage_multiplier(
+        weapon_hyperarmor,
+        &weapon_poise_damage.class,
+        armor_poise,
+        is_in_recovery,
+        selected_modifiers,
+        &MODIFIERS,
+    );
+
+    Some(ComparisonCell {
+        poise_damage: poise_damage_values.total() as f64
+            * MODIFIERS.combined_factor(selected_modifiers, ModifierTarget::OutgoingPoiseDamage),
+        hyperarmor,
+    })
+}
+
+impl ComparisonCell {
+    fn text(&self) -> String {
+        let mut text = format!("{:.1}", self.poise_damage);
+
+        if self.hyperarmor > 0.0 {
+            text.push_str(&format!(" (HA {:.1})", self.hyperarmor));
+        }
+
+        text
+    }
+}
+
+type ComparisonRow = (Attacks, Vec<Option<ComparisonCell>>);
+
+fn comparison_table(weapons: &[String], rows: &[ComparisonRow]) -> Table {
+    let columns = std::iter::once("Attack")
+        .chain(weapons.iter().map(|weapon| weapon.as_str()))
+        .collect::<Vec<_>>();
+
+    let mut table = Table::new("Weapon Comparison", &columns);
+
+    for (attack, cells) in rows {
+        table.push_row(
+            std::iter::once(attack.to_string())
+                .chain(cells.iter().map(|cell| match cell {
+                    Some(cell) => cell.text(),
+                    None => "-".to_string(),
+                }))
+                .collect(),
+        );
+    }
+
+    table
+}
+
+#[derive(Default)]
+pub struct ComparisonView {
+    pub is_open: bool,
+    weapons: Vec<String>,
+    weapon_select_view: WeaponSelectView,
+    table_export_controls: TableExportControls,
+}
+
+impl ComparisonView {
+    pub fn new() -> Self {
+        Self {
+            is_open: false,
+            weapons: Vec::new(),
+            weapon_select_view: WeaponSelectView::new(),
+            table_export_controls: TableExportControls::default(),
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, equipment_view: &EquipmentView) {
+        let EquipmentView {
+            armor_poise,
+            is_in_recovery,
+            selected_modifiers,
+            ..
+        } = equipment_view;
+
+        egui::Window::new("Weapon Comparison")
+            .id("Weapon Comparison Window".into())
+            .resizable(true)
+            .title_bar(true)
+            .open(&mut self.is_open)
+            .show(ui.ctx(), |ui| {
+                ui.button("Add Weapon").clicked().then(|| {
+                    self.weapon_select_view.is_open = true;
+                });
+
+                if self.weapon_select_view.is_open {
+                    self.weapon_select_view.show(ui, "Compared");
+                }
+
+                if let Some(selected_weapon) = self.weapon_select_view.selected_weapon.take() {
+                    if !self.weapons.contains(&selected_weapon) {
+                        self.weapons.push(selected_weapon);
+                    }
+                }
+
+                if self.weapons.is_empty() {
+                    return;
+                }
+
+                let rows = Attacks::all()
+                    .into_iter()
+                    .map(|attack| {
+                        let cells = self
+                            .weapons
+                            .iter()
+                            .map(|weapon| {
+                                comparison_cell(
+                                    weapon,
+                                    &attack,
+                                    *armor_poise,
+                                    *is_in_recovery,
+                                    selected_modifiers,
+                                )
+                            })
+                            .collect::<Vec<_>>();
+
+                        (attack, cells)
+                    })
+                    .filter(|(_, cells)| cells.iter().any(|cell| cell.is_some()))
+                    .collect::<Vec<ComparisonRow>>();
+
+                if let Some(export) = self.table_export_controls.show(ui) {
+                    let table = comparison_table(&self.weapons, &rows).with_loadout(equipment_view);
+
+                    self.table_export_controls
+                        .export(ui, export, "weapon_comparison", &table);
+                }
+
+                let mut removed_weapon = None;
+
+                TableBuilder::new(ui)
+                    .column(Column::auto().resizable(true)) // Attack
+                    .columns(Column::auto().resizable(true), self.weapons.len())
+                    .cell_layout(Layout::centered_and_justified(egui::Direction::TopDown))
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            ui.label("Attack");
+                        });
+                        for (i, weapon) in self.weapons.iter().enumerate() {
+                            header.col(|ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label(weapon);
+                                    ui.small_button("✖").clicked().then(|| {
+                                        removed_weapon = Some(i);
+                                    });
+                                });
+                            });
+                        }
+                    })
+                    .body(|body| {
+                        body.rows(20.0, rows.len(), |mut row| {
+                            let (attack, cells) = &rows[row.index()];
+
+                            let best_and_worst = best_and_worst(
+                                &cells
+                                    .iter()
+                                    .map(|cell| cell.as_ref().map(|cell| cell.poise_damage))
+                                    .collect::<Vec<_>>(),
+                            );
+
+                            row.col(|ui| {
+                                ui.label(attack.to_string());
+                            });
+
+                            for (i, cell) in cells.iter().enumerate() {
+                                row.col(|ui| {
+                                    let Some(cell) = cell else {
+                                        ui.label("-");
+                                        return;
+                                    };
+
+                                    let text = cell.text();
+
+                                    let text = match best_and_worst {
+                                        Some((best, _)) if best == i => {
+                                            RichText::new(text).color(egui::Color32::GREEN)
+                                        }
+                                        Some((_, worst)) if worst == i => {
+                                            RichText::new(text).color(egui::Color32::RED)
+                                        }
+                                        _ => RichText::new(text),
+                                    };
+
+                                    ui.label(text);
+                                });
+                            }
+                        });
+                    });
+
+                if let Some(removed_weapon) = removed_weapon {
+                    self.weapons.remove(removed_weapon);
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_best_and_worst() {
+        assert_eq!(
+            best_and_worst(&[Some(10.0), None, Some(30.0), Some(20.0)]),
+            Some((2, 0))
+        );
+        assert_eq!(best_and_worst(&[Some(10.0), Some(10.0)]), None);
+        assert_eq!(best_and_worst(&[None, Some(10.0)]), None);
+        assert_eq!(best_and_worst(&[]), None);
+    }
+}
@@ -0,0 +1,343 @@
+use egui::{Color32, Slider};
+use egui_plot::{Bar, BarChart, Legend, Line, Plot, VLine};
+
+use crate::{
+    data::Attacks,
+    equipment_view::{effective_poise, EquipmentView},
+    plot_export::{Chart, ChartItem, ExportControls},
+    selection::{SelectionBus, SelectionEvent},
+    static_data::POISE_DATA,
+};
+
+/// Share of `poise_damage_values` below `poise`, i.e. tanked
+pub fn tanked_share(poise_damage_values: &[f64], poise: f64) -> Option<f64> {
+    if poise_damage_values.is_empty() {
+        return None;
+    }
+
+    let tanked = poise_damage_values
+        .iter()
+        .filter(|poise_damage| **poise_damage < poise)
+        .count();
+
+    Some(tanked as f64 / poise_damage_values.len() as f64)
+}
+
+/// Start of every non-empty bin and the number of values in it
+pub fn histogram(poise_damage_values: &[f64], bin_width: f64) -> Vec<(f64, usize)> {
+    let mut bins = Vec::<(f64, usize)>::new();
+
+    let mut sorted_values = poise_damage_values.to_vec();
+    sorted_values.sort_by(f64::total_cmp);
+
+    for poise_damage in sorted_values {
+        let bin_start = (poise_damage / bin_width).floor() * bin_width;
+
+        match bins.last_mut() {
+            Some((start, count)) if *start == bin_start => *count += 1,
+            _ => bins.push((bin_start, 1)),
+        }
+    }
+
+    bins
+}
+
+// Example
+// [10, 20, 20] gives [0, 0], [10, 0], [10, 33.3], [20, 33.3], [20, 100]
+// the curve steps up right after each value, as poise has to be above it to tank it
+pub fn coverage_curve(poise_damage_values: &[f64]) -> Vec<[f64; 2]> {
+    let mut sorted_values = poise_damage_values.to_vec();
+    sorted_values.sort_by(f64::total_cmp);
+
+    let count = sorted_values.len() as f64;
+
+    let mut curve = vec![[0.0, 0.0]];
+
+    for (i, poise_damage) in sorted_values.iter().enumerate() {
+        if sorted_values.get(i + 1) == Some(poise_damage) {
+            continue;
+        }
+
+        let previous_share = curve.last().unwrap()[1];
+        let share = (i + 1) as f64 / count * 100.0;
+
+        curve.push([*poise_damage, previous_share]);
+        curve.push([*poise_damage, share]);
+    }
+
+    curve
+}
+
+/// Armor poise and, if the attack has any, hyperarmor as vertical lines
+fn poise_lines(hyperarmor: f64, armor_poise: f64) -> Vec<ChartItem> {
+    let mut poise_lines = vec![ChartItem::VLine {
+        name: Some("Armor Poise".to_string()),
+        color: Color32::from_rgb(255, 0, 0),
+        is_dashed: false,
+        x: armor_poise,
+    }];
+
+    if hyperarmor > 0.0 {
+        poise_lines.push(ChartItem::VLine {
+            name: Some("Hyperarmor".to_string()),
+            color: Color32::from_rgb(0, 255, 0),
+            is_dashed: false,
+            x: hyperarmor,
+        });
+    }
+
+    poise_lines
+}
+
+pub fn histogram_chart(
+    poise_damage_values: &[f64],
+    bin_width: f64,
+    attack: &Attacks,
+    hyperarmor: f64,
+    armor_poise: f64,
+) -> Chart {
+    let mut chart = Chart::new(
+        format!("Poise Damage of {}", attack),
+        "Poise Damage",
+        "Weapons",
+    );
+
+    chart.items.push(ChartItem::Bars {
+        name: Some("Weapons".to_string()),
+        color: Color32::from_rgb(100, 150, 250),
+        width: bin_width,
+        bars: histogram(poise_damage_values, bin_width)
+            .into_iter()
+            .map(|(bin_start, count)| [bin_start + bin_width / 2.0, count as f64])
+            .collect(),
+    });
+    chart.items.extend(poise_lines(hyperarmor, armor_poise));
+
+    chart
+}
+
+pub fn coverage_curve_chart(
+    poise_damage_values: &[f64],
+    attack: &Attacks,
+    hyperarmor: f64,
+    armor_poise: f64,
+) -> Chart {
+    let mut chart = Chart::new(
+        format!("Weapons Tanked by Poise for {}", attack),
+        "Poise",
+        "Weapons Tanked %",
+    );
+
+    chart.items.push(ChartItem::Line {
+        name: Some("Tanked".to_string()),
+        color: Color32::from_rgb(100, 150, 250),
+        is_dashed: false,
+        points: coverage_curve(poise_damage_values),
+    });
+    chart.items.extend(poise_lines(hyperarmor, armor_poise));
+
+    chart
+}
+
+pub struct CoverageView {
+    pub is_open: bool,
+    selected_attack: Option<Attacks>,
+    bin_width: f64,
+    export_controls: ExportControls,
+}
+
+impl Default for CoverageView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CoverageView {
+    pub fn new() -> Self {
+        Self {
+            is_open: false,
+            selected_attack: Some(Attacks::TwoHandedR1Chain(0)),
+            bin_width: 5.0,
+            export_controls: ExportControls::default(),
+        }
+    }
+
+    pub fn on_selection_event(&mut self, event: &SelectionEvent) {
+        if let SelectionEvent::AttackSelected(attack) = event {
+            self.selected_attack = Some(attack.clone());
+        }
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        equipment_view: &EquipmentView,
+        selection_bus: &mut SelectionBus,
+    ) {
+        const INITIAL_WINDOW_SIZE: [f32; 2] = [600.0, 500.0];
+
+        let multiplier = equipment_view
+            .incoming_poise_damage_multiplier
+            .unwrap_or(1.0);
+        let hyperarmor = equipment_view.hyperarmor.unwrap_or_default();
+        let armor_poise = equipment_view.armor_poise as f64;
+
+        egui::Window::new("Coverage")
+            .id("Coverage Window".into())
+            .resizable(true)
+            .title_bar(true)
+            .default_size(INITIAL_WINDOW_SIZE)
+            .open(&mut self.is_open)
+            .show(ui.ctx(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Incoming Attack: ");
+
+                    if Attacks::combobox(ui, &mut self.selected_attack) {
+                        if let Some(attack) = &self.selected_attack {
+                            selection_bus.publish(SelectionEvent::AttackSelected(attack.clone()));
+                        }
+                    }
+                });
+
+                ui.add(Slider::new(&mut self.bin_width, 1.0..=20.0).text("Bin Width"));
+
+                let Some(attack) = &self.selected_attack else {
+                    return;
+                };
+
+                let poise_damage_values =
+                    POISE_DATA.get_poise_damage_values_for_attack(attack, multiplier);
+
+                let poise = effective_poise(hyperarmor, equipment_view.armor_poise);
+
+                ui.separator();
+
+                match tanked_share(&poise_damage_values, poise) {
+                    Some(share) => {
+                        ui.heading(format!(
+                            "You tank {:.0}% of weapons' {}",
+                            share * 100.0,
+                            attack
+                        ));
+
+                        if hyperarmor > 0.0 {
+                            ui.label(format!(
+                                "Armor poise alone tanks {:.0}%",
+                                tanked_share(&poise_damage_values, armor_poise).unwrap() * 100.0
+                            ));
+                        }
+                    }
+                    None => {
+                        ui.label(format!("No weapon has poise damage for {}", attack));
+                        return;
+                    }
+                };
+
+                if let Some(format) = self.export_controls.show(ui) {
+                    let charts = [
+                        (
+                            "coverage_histogram",
+                            histogram_chart(
+                                &poise_damage_values,
+                                self.bin_width,
+                                attack,
+                                hyperarmor,
+                                armor_poise,
+                            ),
+                        ),
+                        (
+                            "coverage_curve",
+                            coverage_curve_chart(
+                                &poise_damage_values,
+                                attack,
+                                hyperarmor,
+                                armor_poise,
+                            ),
+                        ),
+                    ];
+
+                    self.export_controls.export(format, &charts);
+                }
+
+                let markers = |plot_ui: &mut egui_plot::PlotUi| {
+                    plot_ui.vline(
+                        VLine::new(armor_poise)
+                            .color(Color32::from_rgb(255, 0, 0))
+                            .name("Armor Poise"),
+                    );
+
+                    if hyperarmor > 0.0 {
+                        plot_ui.vline(
+                            VLine::new(hyperarmor)
+                                .color(Color32::from_rgb(0, 255, 0))
+                                .name("Hyperarmor"),
+                        );
+                    }
+                };
+
+                let plot_height = (ui.available_height() / 2.0 - 10.0).max(100.0);
+
+                Plot::new("Coverage Histogram")
+                    .height(plot_height)
+                    .legend(Legend::default())
+                    .x_axis_label("Poise Damage")
+                    .y_axis_label("Weapons")
+                    .show(ui, |plot_ui| {
+                        let bars = histogram(&poise_damage_values, self.bin_width)
+                            .into_iter()
+                            .map(|(bin_start, count)| {
+                                Bar::new(bin_start + self.bin_width / 2.0, count as f64)
+                                    .width(self.bin_width)
+                            })
+                            .collect();
+
+                        plot_ui.bar_chart(BarChart::new(bars).name("Weapons"));
+
+                        markers(plot_ui);
+                    });
+
+                Plot::new("Coverage Curve")
+                    .height(plot_height)
+                    .legend(Legend::default())
+                    .include_y(0.0)
+                    .include_y(100.0)
+                    .x_axis_label("Poise")
+                    .y_axis_label("Weapons Tanked %")
+                    .show(ui, |plot_ui| {
+                        plot_ui
+                            .line(Line::new(coverage_curve(&poise_damage_values)).name("Tanked"));
+
+                        markers(plot_ui);
+                    });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tanked_share() {
+        assert_eq!(tanked_share(&[], 50.0), None);
+        assert_eq!(tanked_share(&[10.0, 50.0, 60.0, 30.0], 50.0), Some(0.5));
+    }
+
+    #[test]
+    fn test_histogram() {
+        assert_eq!(
+            histogram(&[12.0, 3.0, 14.0, 4.0, 31.0], 10.0),
+            vec![(0.0, 2), (10.0, 2), (30.0, 1)]
+        );
+    }
+
+    #[test]
+    fn test_coverage_curve() {
+        let curve = coverage_curve(&[20.0, 10.0, 20.0]);
+
+        assert_eq!(curve.len(), 5);
+        assert_eq!(curve[2][0], 10.0);
+        assert!((curve[2][1] - 100.0 / 3.0).abs() < 1e-9);
+        assert_eq!(curve[4], [20.0, 100.0]);
+    }
+}
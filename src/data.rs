@@ -1,12 +1,12 @@
 use std::{
     collections::BTreeMap,
     fmt::{Display, Formatter},
+    path::Path,
 };
 
 use convert_case::Casing;
-use egui::Layout;
-use egui_extras::{Column, TableBuilder};
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use strum::{EnumIter, IntoEnumIterator};
 
 pub const POISE_DATA_FILE: &str = "poise_data.csv";
@@ -15,7 +15,7 @@ pub type PoiseData = BTreeMap<String, WeaponPoiseDamage>;
 
 pub type PoiseDamage = u16;
 
-#[derive(Default, Debug, Clone, PartialEq, EnumIter)]
+#[derive(Default, Debug, Clone, PartialEq, EnumIter, Serialize, Deserialize)]
 pub enum CriticalSize {
     #[default]
     Default,
@@ -23,7 +23,7 @@ pub enum CriticalSize {
     Large,
 }
 
-#[derive(Debug, Default, PartialEq, EnumIter, Clone)]
+#[derive(Debug, Default, PartialEq, EnumIter, Clone, Serialize, Deserialize)]
 pub enum Attacks {
     #[default]
     None,
@@ -115,27 +115,42 @@ impl Attacks {
         }
     }
 
-    pub fn combobox(ui: &mut egui::Ui, selected_attack: &mut Option<Attacks>) -> bool {
-        let mut has_attack_changed = false;
+    /// Every attack including each step of chains and every critical size
+    pub fn all() -> Vec<Attacks> {
+        Attacks::iter()
+            .flat_map(|attack| match attack {
+                Attacks::None => vec![],
+                Attacks::OneHandedR1Chain(_) => (0..6).map(Attacks::OneHandedR1Chain).collect(),
+                Attacks::OneHandedR2Chain(_) => (0..2).map(Attacks::OneHandedR2Chain).collect(),
+                Attacks::OneHandedR2Charged(_) => (0..2).map(Attacks::OneHandedR2Charged).collect(),
+                Attacks::OneHandedR2Feint(_) => (0..2).map(Attacks::OneHandedR2Feint).collect(),
+                Attacks::TwoHandedR1Chain(_) => (0..6).map(Attacks::TwoHandedR1Chain).collect(),
+                Attacks::TwoHandedR2Chain(_) => (0..2).map(Attacks::TwoHandedR2Chain).collect(),
+                Attacks::TwoHandedR2Charged(_) => (0..2).map(Attacks::TwoHandedR2Charged).collect(),
+                Attacks::TwoHandedR2Feint(_) => (0..2).map(Attacks::TwoHandedR2Feint).collect(),
+                Attacks::PairedL1Chain(_) => (0..6).map(Attacks::PairedL1Chain).collect(),
+                Attacks::OffHandR1Chain(_) => (0..6).map(Attacks::OffHandR1Chain).collect(),
+                Attacks::Backstab(_) => CriticalSize::iter().map(Attacks::Backstab).collect(),
+                Attacks::Riposte(_) => CriticalSize::iter().map(Attacks::Riposte).collect(),
+                attack => vec![attack],
+            })
+            .collect()
+    }
 
-        egui::ComboBox::from_label("")
-            .selected_text(format!(
-                "{}",
-                selected_attack.as_ref().unwrap_or(&Attacks::None)
-            ))
-            .show_ui(ui, |ui| {
-                for attack in Attacks::iter() {
-                    let attack_str = format!("{}", attack);
+    /// Looks an attack up by its `Debug` name, e.g. `TwoHandedR1Chain(0)`, ignoring case, spaces and punctuation
+    pub fn from_name(name: &str) -> Option<Attacks> {
+        let normalize = |name: &str| {
+            name.chars()
+                .filter(char::is_ascii_alphanumeric)
+                .collect::<String>()
+                .to_lowercase()
+        };
 
-                    ui.selectable_value(selected_attack, Some(attack), attack_str.to_string())
-                        .clicked()
-                        .then(|| {
-                            has_attack_changed = true;
-                        });
-                }
-            });
+        let name = normalize(name);
 
-        has_attack_changed
+        Attacks::all()
+            .into_iter()
+            .find(|attack| normalize(&format!("{:?}", attack)) == name)
     }
 }
 
@@ -153,6 +168,44 @@ impl ApplyMultiplier for PoiseDamageValues {
     }
 }
 
+impl PoiseDamageValues {
+    pub fn total(&self) -> PoiseDamage {
+        self.0.iter().sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn total_to_string(&self) -> String {
+        match self.is_empty() {
+            true => String::new(),
+            false => self.total().to_string(),
+        }
+    }
+
+    /// Whether a single hit, or only all hits combined, break `poise`
+    pub fn threshold_status(&self, poise: f64) -> Option<ThresholdStatus> {
+        let max_hit = *self.0.iter().max()? as f64;
+
+        if max_hit >= poise {
+            Some(ThresholdStatus::Staggered)
+        } else if self.total() as f64 >= poise {
+            Some(ThresholdStatus::StaggeredByTotal)
+        } else {
+            Some(ThresholdStatus::Tanked)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, strum::Display, Serialize)]
+pub enum ThresholdStatus {
+    Tanked,
+    #[strum(to_string = "Staggered By Total")]
+    StaggeredByTotal,
+    Staggered,
+}
+
 impl Display for PoiseDamageValues {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let values = self.0.iter().map(|n| n.to_string()).join(" + ");
@@ -175,7 +228,7 @@ impl From<&str> for PoiseDamageValues {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct WeaponPoiseDamage {
     pub name: String,
     pub class: String,
@@ -252,6 +305,18 @@ impl WeaponPoiseDamage {
         }
     }
 
+    /// Every attack of `Attacks::all` this weapon has poise damage values for
+    pub fn attacks(&self) -> impl Iterator<Item = (Attacks, &PoiseDamageValues)> {
+        Attacks::all().into_iter().filter_map(|attack| {
+            let poise_damage_values = self.get_poise_damage_by_attack(&attack)?;
+
+            match poise_damage_values.is_empty() {
+                true => None,
+                false => Some((attack, poise_damage_values)),
+            }
+        })
+    }
+
     pub fn get_poise_damage_by_attack_w_multiplier(
         &self,
         attack: &Attacks,
@@ -267,7 +332,29 @@ impl WeaponPoiseDamage {
     }
 }
 
-#[derive(Debug, Clone)]
+#[cfg(test)]
+impl WeaponPoiseDamage {
+    /// Weapon without any poise damage, for building test fixtures
+    pub fn test_weapon(name: &str, class: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            class: class.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the hits of the attack picked by `attack`, e.g. `|w| &mut w.one_handed.r1.chain.one`
+    pub fn with_hits(
+        mut self,
+        attack: impl FnOnce(&mut Self) -> &mut PoiseDamageValues,
+        hits: Vec<PoiseDamage>,
+    ) -> Self {
+        *attack(&mut self) = hits.into();
+        self
+    }
+}
+
+#[derive(Debug, Default, Clone)]
 pub struct Grip {
     pub r1: Strength,
     pub r2: Strength,
@@ -282,7 +369,7 @@ impl ApplyMultiplier for Grip {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct Strength {
     pub chain: Chain,
     pub charged: Chain,
@@ -331,6 +418,23 @@ impl Chain {
             _ => None,
         }
     }
+
+    /// Each step of the chain summed up to a single value
+    pub fn totals(&self) -> Chain {
+        let total = |values: &PoiseDamageValues| match values.is_empty() {
+            true => PoiseDamageValues::default(),
+            false => vec![values.total()].into(),
+        };
+
+        Chain {
+            one: total(&self.one),
+            two: total(&self.two),
+            three: total(&self.three),
+            four: total(&self.four),
+            five: total(&self.five),
+            six: total(&self.six),
+        }
+    }
 }
 
 impl ApplyMultiplier for Chain {
@@ -396,7 +500,7 @@ impl From<Chain> for String {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct Size {
     pub default: PoiseDamageValues,
     pub small: PoiseDamageValues,
@@ -413,8 +517,37 @@ impl ApplyMultiplier for Size {
     }
 }
 
-pub fn load_data() -> PoiseData {
-    let mut rdr = csv::Reader::from_path(POISE_DATA_FILE).unwrap();
+#[derive(Debug)]
+pub enum LoadDataError {
+    Csv(csv::Error),
+    MissingColumn { line: u64, column: usize },
+}
+
+impl Display for LoadDataError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadDataError::Csv(error) => write!(f, "{}", error),
+            LoadDataError::MissingColumn { line, column } => {
+                write!(f, "Line {} has no column {}", line, column + 1)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadDataError {}
+
+impl From<csv::Error> for LoadDataError {
+    fn from(error: csv::Error) -> Self {
+        LoadDataError::Csv(error)
+    }
+}
+
+pub fn load_data() -> Result<PoiseData, LoadDataError> {
+    load_data_from(Path::new(POISE_DATA_FILE))
+}
+
+pub fn load_data_from(path: &Path) -> Result<PoiseData, LoadDataError> {
+    let mut rdr = csv::Reader::from_path(path)?;
 
     let mut data = PoiseData::new();
 
@@ -424,21 +557,28 @@ pub fn load_data() -> PoiseData {
     let records_iter = records_iter.skip(5).take(1);
 
     for record in records_iter {
-        let record = record.unwrap();
+        let record = record?;
 
-        let class = record.get(0).unwrap().to_string();
-        let name = record.get(1).unwrap().to_string();
+        let field = |column: usize| {
+            record.get(column).ok_or(LoadDataError::MissingColumn {
+                line: record.position().map_or(0, |position| position.line()),
+                column,
+            })
+        };
+
+        let class = field(0)?.to_string();
+        let name = field(1)?.to_string();
 
         if name.is_empty() || class.is_empty() {
             continue;
         }
 
-        let one_handed_r1_1 = record.get(2).unwrap().into();
-        let one_handed_r1_2 = record.get(3).unwrap().into();
-        let one_handed_r1_3 = record.get(4).unwrap().into();
-        let one_handed_r1_4 = record.get(5).unwrap().into();
-        let one_handed_r1_5 = record.get(6).unwrap().into();
-        let one_handed_r1_6 = record.get(7).unwrap().into();
+        let one_handed_r1_1 = field(2)?.into();
+        let one_handed_r1_2 = field(3)?.into();
+        let one_handed_r1_3 = field(4)?.into();
+        let one_handed_r1_4 = field(5)?.into();
+        let one_handed_r1_5 = field(6)?.into();
+        let one_handed_r1_6 = field(7)?.into();
 
         let one_handed_r1_chain = Chain {
             one: one_handed_r1_1,
@@ -449,8 +589,8 @@ pub fn load_data() -> PoiseData {
             six: one_handed_r1_6,
         };
 
-        let one_handed_r2_1 = record.get(8).unwrap().into();
-        let one_handed_r2_2 = record.get(9).unwrap().into();
+        let one_handed_r2_1 = field(8)?.into();
+        let one_handed_r2_2 = field(9)?.into();
 
         let one_handed_r2_chain = Chain {
             one: one_handed_r2_1,
@@ -458,8 +598,8 @@ pub fn load_data() -> PoiseData {
             ..Default::default()
         };
 
-        let one_handed_charged_r2_1 = record.get(10).unwrap().into();
-        let one_handed_charged_r2_2 = record.get(11).unwrap().into();
+        let one_handed_charged_r2_1 = field(10)?.into();
+        let one_handed_charged_r2_2 = field(11)?.into();
 
         let one_handed_charged_r2_chain = Chain {
             one: one_handed_charged_r2_1,
@@ -467,24 +607,24 @@ pub fn load_data() -> PoiseData {
             ..Default::default()
         };
 
-        let one_handed_running_r1 = record.get(12).unwrap().into();
-        let one_handed_running_r2 = record.get(13).unwrap().into();
+        let one_handed_running_r1 = field(12)?.into();
+        let one_handed_running_r2 = field(13)?.into();
 
-        let one_handed_rolling_r1 = record.get(14).unwrap().into();
+        let one_handed_rolling_r1 = field(14)?.into();
 
-        let one_handed_backstep_r1 = record.get(15).unwrap().into();
+        let one_handed_backstep_r1 = field(15)?.into();
 
-        let one_handed_jumping_r1 = record.get(16).unwrap().into();
-        let one_handed_jumping_r2 = record.get(17).unwrap().into();
+        let one_handed_jumping_r1 = field(16)?.into();
+        let one_handed_jumping_r2 = field(17)?.into();
 
-        let one_handed_guard_counter = record.get(18).unwrap().into();
+        let one_handed_guard_counter = field(18)?.into();
 
-        let two_handed_r1_1 = record.get(19).unwrap().into();
-        let two_handed_r1_2 = record.get(20).unwrap().into();
-        let two_handed_r1_3 = record.get(21).unwrap().into();
-        let two_handed_r1_4 = record.get(22).unwrap().into();
-        let two_handed_r1_5 = record.get(23).unwrap().into();
-        let two_handed_r1_6 = record.get(24).unwrap().into();
+        let two_handed_r1_1 = field(19)?.into();
+        let two_handed_r1_2 = field(20)?.into();
+        let two_handed_r1_3 = field(21)?.into();
+        let two_handed_r1_4 = field(22)?.into();
+        let two_handed_r1_5 = field(23)?.into();
+        let two_handed_r1_6 = field(24)?.into();
 
         let two_handed_r1_chain = Chain {
             one: two_handed_r1_1,
@@ -495,8 +635,8 @@ pub fn load_data() -> PoiseData {
             six: two_handed_r1_6,
         };
 
-        let two_handed_r2_1 = record.get(25).unwrap().into();
-        let two_handed_r2_2 = record.get(26).unwrap().into();
+        let two_handed_r2_1 = field(25)?.into();
+        let two_handed_r2_2 = field(26)?.into();
 
         let two_handed_r2_chain = Chain {
             one: two_handed_r2_1,
@@ -504,8 +644,8 @@ pub fn load_data() -> PoiseData {
             ..Default::default()
         };
 
-        let two_handed_charged_r2_1 = record.get(27).unwrap().into();
-        let two_handed_charged_r2_2 = record.get(28).unwrap().into();
+        let two_handed_charged_r2_1 = field(27)?.into();
+        let two_handed_charged_r2_2 = field(28)?.into();
 
         let two_handed_charged_r2_chain = Chain {
             one: two_handed_charged_r2_1,
@@ -513,24 +653,24 @@ pub fn load_data() -> PoiseData {
             ..Default::default()
         };
 
-        let two_handed_running_r1 = record.get(29).unwrap().into();
-        let two_handed_running_r2 = record.get(30).unwrap().into();
+        let two_handed_running_r1 = field(29)?.into();
+        let two_handed_running_r2 = field(30)?.into();
 
-        let two_handed_rolling_r1 = record.get(31).unwrap().into();
+        let two_handed_rolling_r1 = field(31)?.into();
 
-        let two_handed_backstep_r1 = record.get(32).unwrap().into();
+        let two_handed_backstep_r1 = field(32)?.into();
 
-        let two_handed_jumping_r1 = record.get(33).unwrap().into();
-        let two_handed_jumping_r2 = record.get(34).unwrap().into();
+        let two_handed_jumping_r1 = field(33)?.into();
+        let two_handed_jumping_r2 = field(34)?.into();
 
-        let two_handed_guard_counter = record.get(35).unwrap().into();
+        let two_handed_guard_counter = field(35)?.into();
 
-        let off_hand_r1_1 = record.get(36).unwrap().into();
-        let off_hand_r1_2 = record.get(37).unwrap().into();
-        let off_hand_r1_3 = record.get(38).unwrap().into();
-        let off_hand_r1_4 = record.get(39).unwrap().into();
-        let off_hand_r1_5 = record.get(40).unwrap().into();
-        let off_hand_r1_6 = record.get(41).unwrap().into();
+        let off_hand_r1_1 = field(36)?.into();
+        let off_hand_r1_2 = field(37)?.into();
+        let off_hand_r1_3 = field(38)?.into();
+        let off_hand_r1_4 = field(39)?.into();
+        let off_hand_r1_5 = field(40)?.into();
+        let off_hand_r1_6 = field(41)?.into();
 
         let off_hand_r1_chain = Chain {
             one: off_hand_r1_1,
@@ -541,21 +681,21 @@ pub fn load_data() -> PoiseData {
             six: off_hand_r1_6,
         };
 
-        let _bs_whiff: PoiseDamageValues = record.get(42).unwrap().into();
+        let _bs_whiff: PoiseDamageValues = field(42)?.into();
 
-        let backstab_default = record.get(43).unwrap().into();
-        let riposte_default = record.get(44).unwrap().into();
+        let backstab_default = field(43)?.into();
+        let riposte_default = field(44)?.into();
 
-        let backstab_small = record.get(45).unwrap().into();
-        let riposte_small = record.get(46).unwrap().into();
+        let backstab_small = field(45)?.into();
+        let riposte_small = field(46)?.into();
 
         let backstab_large = PoiseDamageValues::default();
-        let riposte_large = record.get(47).unwrap().into();
+        let riposte_large = field(47)?.into();
 
-        let shieldpoke = record.get(48).unwrap().into();
+        let shieldpoke = field(48)?.into();
 
-        let one_handed_feint_1 = record.get(49).unwrap().into();
-        let one_handed_feint_2 = record.get(50).unwrap().into();
+        let one_handed_feint_1 = field(49)?.into();
+        let one_handed_feint_2 = field(50)?.into();
 
         let one_handed_feint_r2_chain = Chain {
             one: one_handed_feint_1,
@@ -563,8 +703,8 @@ pub fn load_data() -> PoiseData {
             ..Default::default()
         };
 
-        let two_handed_feint_1 = record.get(51).unwrap().into();
-        let two_handed_feint_2 = record.get(52).unwrap().into();
+        let two_handed_feint_1 = field(51)?.into();
+        let two_handed_feint_2 = field(52)?.into();
 
         let two_handed_feint_chain = Chain {
             one: two_handed_feint_1,
@@ -572,12 +712,12 @@ pub fn load_data() -> PoiseData {
             ..Default::default()
         };
 
-        let paired_l1_1 = record.get(53).unwrap().into();
-        let paired_l1_2 = record.get(54).unwrap().into();
-        let paired_l1_3 = record.get(55).unwrap().into();
-        let paired_l1_4 = record.get(56).unwrap().into();
-        let paired_l1_5 = record.get(57).unwrap().into();
-        let paired_l1_6 = record.get(58).unwrap().into();
+        let paired_l1_1 = field(53)?.into();
+        let paired_l1_2 = field(54)?.into();
+        let paired_l1_3 = field(55)?.into();
+        let paired_l1_4 = field(56)?.into();
+        let paired_l1_5 = field(57)?.into();
+        let paired_l1_6 = field(58)?.into();
 
         let paired_l1_chain = Chain {
             one: paired_l1_1,
@@ -588,13 +728,13 @@ pub fn load_data() -> PoiseData {
             six: paired_l1_6,
         };
 
-        let paired_running_l1 = record.get(59).unwrap().into();
+        let paired_running_l1 = field(59)?.into();
 
-        let paired_rolling_l1 = record.get(60).unwrap().into();
+        let paired_rolling_l1 = field(60)?.into();
 
-        let paired_backstep_l1 = record.get(61).unwrap().into();
+        let paired_backstep_l1 = field(61)?.into();
 
-        let paired_jumping_l1 = record.get(62).unwrap().into();
+        let paired_jumping_l1 = field(62)?.into();
 
         let one_handed_r1 = Strength {
             chain: one_handed_r1_chain,
@@ -699,9 +839,10 @@ pub fn load_data() -> PoiseData {
         data.insert(key, weapon);
     }
 
-    data.into_iter()
+    Ok(data
+        .into_iter()
         .sorted_by(|(a, _), (b, _)| a.cmp(b))
-        .collect()
+        .collect())
 }
 
 #[cfg(test)]
@@ -709,7 +850,7 @@ mod test_load_data {
 
     #[test]
     fn test() {
-        let data = super::load_data();
+        let data = super::load_data().unwrap();
 
         dbg!(&data);
 
@@ -721,6 +862,33 @@ mod test_load_data {
     }
 }
 
+#[cfg(test)]
+mod test_load_data_from {
+    use super::{load_data_from, LoadDataError};
+
+    #[test]
+    fn test() {
+        let path = std::env::temp_dir().join("hyperarmor_inspector_short_poise_data.csv");
+        // Tests only read the sixth record
+        std::fs::write(
+            &path,
+            format!("Class,Name,1h R1 1\n{}", "Dagger,Dagger,40\n".repeat(6)),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            load_data_from(&path),
+            Err(LoadDataError::MissingColumn { line: 7, column: 3 })
+        ));
+        assert!(matches!(
+            load_data_from(&std::env::temp_dir().join("hyperarmor_inspector_missing.csv")),
+            Err(LoadDataError::Csv(_))
+        ));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
 // Example
 // "10" to [10]
 // "10 + 10" to [10, 10]
@@ -744,6 +912,27 @@ pub fn poise_string_to_numbers(s: &str) -> Vec<PoiseDamage> {
         .collect()
 }
 
+#[cfg(test)]
+mod test_threshold_status {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let values = PoiseDamageValues(vec![30, 30]);
+
+        assert_eq!(
+            values.threshold_status(25.0),
+            Some(ThresholdStatus::Staggered)
+        );
+        assert_eq!(
+            values.threshold_status(50.0),
+            Some(ThresholdStatus::StaggeredByTotal)
+        );
+        assert_eq!(values.threshold_status(61.0), Some(ThresholdStatus::Tanked));
+        assert_eq!(PoiseDamageValues::default().threshold_status(0.0), None);
+    }
+}
+
 #[cfg(test)]
 mod test_poise_string_to_numbers {
     use super::*;
@@ -758,108 +947,435 @@ mod test_poise_string_to_numbers {
     }
 }
 
-mod ui {
+#[cfg(test)]
+mod test_attack_from_name {
     use super::*;
 
+    #[test]
+    fn test() {
+        assert_eq!(
+            Attacks::from_name("TwoHandedR1Chain(2)"),
+            Some(Attacks::TwoHandedR1Chain(2))
+        );
+        assert_eq!(
+            Attacks::from_name("two handed r2 running"),
+            Some(Attacks::TwoHandedR2Running)
+        );
+        assert_eq!(
+            Attacks::from_name("Backstab (Small)"),
+            Some(Attacks::Backstab(CriticalSize::Small))
+        );
+        assert_eq!(Attacks::from_name("Claymore"), None);
+    }
+}
+
+#[cfg(test)]
+mod test_weapon_poise_damage_table {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let weapon_poise_damage = WeaponPoiseDamage::test_weapon("Claymore", "Greatsword")
+            .with_hits(|w| &mut w.two_handed.r1.running, vec![30, 30]);
+
+        let table = weapon_poise_damage.table(Some(51.0));
+
+        assert_eq!(table.title, "Claymore");
+        assert_eq!(table.columns.len(), 5);
+        assert!(table.rows.contains(&vec![
+            "Two Handed".to_string(),
+            "R1 Running".to_string(),
+            "30 + 30".to_string(),
+            "60".to_string(),
+            "+9.0".to_string(),
+        ]));
+        assert_eq!(weapon_poise_damage.table(None).columns.len(), 4);
+    }
+}
+
+mod table {
+    use super::*;
+    use crate::table_export::Table;
+
+    #[derive(Clone, Copy)]
+    pub(super) enum PoiseDamageCell<'a> {
+        Values(&'a PoiseDamageValues),
+        Chain(&'a Chain),
+    }
+
+    impl PoiseDamageCell<'_> {
+        pub(super) fn per_hit(&self) -> String {
+            match self {
+                PoiseDamageCell::Values(values) => values.to_string(),
+                PoiseDamageCell::Chain(chain) => chain.to_string(),
+            }
+        }
+
+        pub(super) fn total(&self) -> String {
+            match self {
+                PoiseDamageCell::Values(values) => values.total_to_string(),
+                PoiseDamageCell::Chain(chain) => chain.totals().to_string(),
+            }
+        }
+
+        pub(super) fn values(&self) -> Vec<&PoiseDamageValues> {
+            match self {
+                PoiseDamageCell::Values(values) => vec![values],
+                PoiseDamageCell::Chain(chain) => (0..6).filter_map(|n| chain.get(n)).collect(),
+            }
+        }
+
+        pub(super) fn margin(&self, poise: f64) -> Option<f64> {
+            self.values()
+                .iter()
+                .filter(|values| !values.is_empty())
+                .map(|values| values.total() as f64 - poise)
+                .max_by(f64::total_cmp)
+        }
+    }
+
+    pub(super) type GripRow<'a> = (&'static str, PoiseDamageCell<'a>, PoiseDamageCell<'a>);
+    pub(super) type AttackRow<'a> = (&'static str, PoiseDamageCell<'a>);
+
     impl WeaponPoiseDamage {
-        pub fn view(&self, ui: &mut egui::Ui) {
-            ui.vertical(|ui| {
-                // ui.label(format!("Name: {}", self.name));
-                // ui.label(format!("Class: {}", self.class));
-
-                TableBuilder::new(ui)
-                    .column(Column::auto().resizable(true)) // Attack Type
-                    .column(Column::auto().resizable(true)) // OneHanded
-                    .column(Column::auto().resizable(true)) // TwoHanded
-                    .cell_layout(Layout::centered_and_justified(egui::Direction::TopDown))
-                    .header(20.0, |mut header| {
-                        header.col(|ui| {
-                            ui.label("Attack Type");
-                        });
-                        header.col(|ui| {
-                            ui.label("One Handed");
-                        });
-                        header.col(|ui| {
-                            ui.label("Two Handed");
-                        });
-                    })
-                    .body(|body| {
-                        let rows = [
-                            [
-                                "R1 Chain".to_string(),
-                                self.one_handed.r1.chain.to_string(),
-                                self.two_handed.r1.chain.to_string(),
-                            ],
-                            [
-                                "R1 Running".to_string(),
-                                self.one_handed.r1.running.to_string(),
-                                self.two_handed.r1.running.to_string(),
-                            ],
-                            [
-                                "R1 Rolling".to_string(),
-                                self.one_handed.r1.rolling.to_string(),
-                                self.two_handed.r1.rolling.to_string(),
-                            ],
-                            [
-                                "R1 Backstep".to_string(),
-                                self.one_handed.r1.backstep.to_string(),
-                                self.two_handed.r1.backstep.to_string(),
-                            ],
-                            [
-                                "R1 Jumping".to_string(),
-                                self.one_handed.r1.jumping.to_string(),
-                                self.two_handed.r1.jumping.to_string(),
-                            ],
-                            [
-                                "R1 Guard Counter".to_string(),
-                                self.one_handed.r1.guard_counter.to_string(),
-                                self.two_handed.r1.guard_counter.to_string(),
-                            ],
-                            [
-                                "R2 Chain".to_string(),
-                                self.one_handed.r2.chain.to_string(),
-                                self.two_handed.r2.chain.to_string(),
-                            ],
-                            [
-                                "R2 Charged".to_string(),
-                                self.one_handed.r2.charged.to_string(),
-                                self.two_handed.r2.charged.to_string(),
-                            ],
-                            [
-                                "R2 Running".to_string(),
-                                self.one_handed.r2.running.to_string(),
-                                self.two_handed.r2.running.to_string(),
-                            ],
-                            [
-                                "R2 Jumping".to_string(),
-                                self.one_handed.r2.jumping.to_string(),
-                                self.two_handed.r2.jumping.to_string(),
-                            ],
-                            [
-                                "R2 Feint".to_string(),
-                                self.one_handed.r2.feint.to_string(),
-                                self.two_handed.r2.feint.to_string(),
-                            ],
-                        ];
-
-                        let total_rows = rows.len();
-
-                        body.rows(30.0, total_rows, |mut row| {
-                            let row_index = row.index();
-
-                            row.col(|ui| {
-                                ui.label(&rows[row_index][0]);
-                            });
+        pub(super) fn grip_rows(&self) -> Vec<GripRow<'_>> {
+            use PoiseDamageCell::{Chain, Values};
+
+            let one_handed = &self.one_handed;
+            let two_handed = &self.two_handed;
+
+            vec![
+                (
+                    "R1 Chain",
+                    Chain(&one_handed.r1.chain),
+                    Chain(&two_handed.r1.chain),
+                ),
+                (
+                    "R1 Running",
+                    Values(&one_handed.r1.running),
+                    Values(&two_handed.r1.running),
+                ),
+                (
+                    "R1 Rolling",
+                    Values(&one_handed.r1.rolling),
+                    Values(&two_handed.r1.rolling),
+                ),
+                (
+                    "R1 Backstep",
+                    Values(&one_handed.r1.backstep),
+                    Values(&two_handed.r1.backstep),
+                ),
+                (
+                    "R1 Jumping",
+                    Values(&one_handed.r1.jumping),
+                    Values(&two_handed.r1.jumping),
+                ),
+                (
+                    "R1 Guard Counter",
+                    Values(&one_handed.r1.guard_counter),
+                    Values(&two_handed.r1.guard_counter),
+                ),
+                (
+                    "R2 Chain",
+                    Chain(&one_handed.r2.chain),
+                    Chain(&two_handed.r2.chain),
+                ),
+                (
+                    "R2 Charged",
+                    Chain(&one_handed.r2.charged),
+                    Chain(&two_handed.r2.charged),
+                ),
+                (
+                    "R2 Running",
+                    Values(&one_handed.r2.running),
+                    Values(&two_handed.r2.running),
+                ),
+                (
+                    "R2 Jumping",
+                    Values(&one_handed.r2.jumping),
+                    Values(&two_handed.r2.jumping),
+                ),
+                (
+                    "R2 Feint",
+                    Chain(&one_handed.r2.feint),
+                    Chain(&two_handed.r2.feint),
+                ),
+            ]
+        }
 
-                            row.col(|ui| {
-                                ui.label(&rows[row_index][1]);
-                            });
+        /// Sections shown below the one handed / two handed table
+        pub(super) fn attack_sections(&self) -> Vec<(&'static str, Vec<AttackRow<'_>>)> {
+            use PoiseDamageCell::{Chain, Values};
+
+            vec![
+                (
+                    "Paired L1",
+                    vec![
+                        ("L1 Chain", Chain(&self.paired.chain)),
+                        ("L1 Running", Values(&self.paired.running)),
+                        ("L1 Rolling", Values(&self.paired.rolling)),
+                        ("L1 Backstep", Values(&self.paired.backstep)),
+                        ("L1 Jumping", Values(&self.paired.jumping)),
+                    ],
+                ),
+                ("Off Hand R1", vec![("R1 Chain", Chain(&self.offhand))]),
+                (
+                    "Criticals",
+                    vec![
+                        ("Backstab", Values(&self.backstab.default)),
+                        ("Backstab Small", Values(&self.backstab.small)),
+                        ("Backstab Large", Values(&self.backstab.large)),
+                        ("Riposte", Values(&self.riposte.default)),
+                        ("Riposte Small", Values(&self.riposte.small)),
+                        ("Riposte Large", Values(&self.riposte.large)),
+                    ],
+                ),
+                ("Shieldpoke", vec![("Shieldpoke", Values(&self.shieldpoke))]),
+            ]
+        }
+
+        /// The rows of `view`, one per grip, with the margin to `poise` if given
+        pub fn table(&self, poise: Option<f64>) -> Table {
+            let mut columns = vec!["Section", "Attack Type", "Per Hit", "Total"];
+
+            if poise.is_some() {
+                columns.push("Margin");
+            }
+
+            let mut table = Table::new(self.name.clone(), &columns);
+
+            if let Some(poise) = poise {
+                table = table.with_metadata("Poise", format!("{:.1}", poise));
+            }
+
+            let grip_rows = self.grip_rows();
+
+            let rows =
+                grip_rows
+                    .iter()
+                    .map(|(attack_type, one_handed, _)| ("One Handed", *attack_type, *one_handed))
+                    .chain(grip_rows.iter().map(|(attack_type, _, two_handed)| {
+                        ("Two Handed", *attack_type, *two_handed)
+                    }))
+                    .chain(
+                        self.attack_sections()
+                            .into_iter()
+                            .flat_map(|(section, rows)| {
+                                rows.into_iter()
+                                    .map(move |(attack_type, cell)| (section, attack_type, cell))
+                            }),
+                    );
+
+            for (section, attack_type, cell) in rows {
+                let mut row = vec![
+                    section.to_string(),
+                    attack_type.to_string(),
+                    cell.per_hit(),
+                    cell.total(),
+                ];
+
+                if let Some(poise) = poise {
+                    row.push(
+                        cell.margin(poise)
+                            .map(|margin| format!("{:+.1}", margin))
+                            .unwrap_or_default(),
+                    );
+                }
+
+                table.push_row(row);
+            }
+
+            table
+        }
+    }
+}
 
-                            row.col(|ui| {
-                                ui.label(&rows[row_index][2]);
+#[cfg(feature = "gui")]
+mod ui {
+    use egui::Layout;
+    use egui_extras::{Column, TableBuilder};
+
+    use super::{
+        table::{AttackRow, GripRow, PoiseDamageCell},
+        *,
+    };
+
+    impl Attacks {
+        pub fn combobox(ui: &mut egui::Ui, selected_attack: &mut Option<Attacks>) -> bool {
+            let mut has_attack_changed = false;
+
+            egui::ComboBox::from_label("")
+                .selected_text(format!(
+                    "{}",
+                    selected_attack.as_ref().unwrap_or(&Attacks::None)
+                ))
+                .show_ui(ui, |ui| {
+                    for attack in Attacks::iter() {
+                        let attack_str = format!("{}", attack);
+
+                        ui.selectable_value(selected_attack, Some(attack), attack_str.to_string())
+                            .clicked()
+                            .then(|| {
+                                has_attack_changed = true;
                             });
-                        });
+                    }
+                });
+
+            has_attack_changed
+        }
+    }
+
+    impl PoiseDamageCell<'_> {
+        /// Worst status of all steps
+        fn threshold_status(&self, poise: f64) -> Option<ThresholdStatus> {
+            self.values()
+                .iter()
+                .filter_map(|values| values.threshold_status(poise))
+                .max()
+        }
+    }
+
+    impl ThresholdStatus {
+        fn color(&self) -> egui::Color32 {
+            match self {
+                ThresholdStatus::Tanked => egui::Color32::from_rgb(60, 160, 60),
+                ThresholdStatus::StaggeredByTotal => egui::Color32::from_rgb(230, 160, 30),
+                ThresholdStatus::Staggered => egui::Color32::from_rgb(220, 50, 50),
+            }
+        }
+    }
+
+    fn poise_damage_cell(
+        ui: &mut egui::Ui,
+        text: String,
+        cell: &PoiseDamageCell,
+        poise: Option<f64>,
+    ) {
+        let Some(poise) = poise else {
+            ui.label(text);
+            return;
+        };
+
+        if let Some(threshold_status) = cell.threshold_status(poise) {
+            ui.painter().rect_filled(
+                ui.max_rect(),
+                0.0,
+                threshold_status.color().gamma_multiply(0.35),
+            );
+        }
+
+        let response = ui.label(text);
+
+        if let Some(margin) = cell.margin(poise) {
+            response.on_hover_text(format!("Margin: {:+.1}", margin));
+        }
+    }
+
+    fn grip_table(ui: &mut egui::Ui, rows: &[GripRow<'_>], poise: Option<f64>) {
+        TableBuilder::new(ui)
+            .vscroll(false)
+            .column(Column::auto().resizable(true)) // Attack Type
+            .column(Column::auto().resizable(true)) // OneHanded
+            .column(Column::auto().resizable(true)) // OneHanded Total
+            .column(Column::auto().resizable(true)) // TwoHanded
+            .column(Column::auto().resizable(true)) // TwoHanded Total
+            .cell_layout(Layout::centered_and_justified(egui::Direction::TopDown))
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.label("Attack Type");
+                });
+                header.col(|ui| {
+                    ui.label("One Handed");
+                });
+                header.col(|ui| {
+                    ui.label("Total");
+                });
+                header.col(|ui| {
+                    ui.label("Two Handed");
+                });
+                header.col(|ui| {
+                    ui.label("Total");
+                });
+            })
+            .body(|body| {
+                body.rows(30.0, rows.len(), |mut row| {
+                    let (attack_type, one_handed, two_handed) = &rows[row.index()];
+
+                    row.col(|ui| {
+                        ui.label(*attack_type);
+                    });
+
+                    row.col(|ui| {
+                        poise_damage_cell(ui, one_handed.per_hit(), one_handed, poise);
                     });
+
+                    row.col(|ui| {
+                        poise_damage_cell(ui, one_handed.total(), one_handed, poise);
+                    });
+
+                    row.col(|ui| {
+                        poise_damage_cell(ui, two_handed.per_hit(), two_handed, poise);
+                    });
+
+                    row.col(|ui| {
+                        poise_damage_cell(ui, two_handed.total(), two_handed, poise);
+                    });
+                });
+            });
+    }
+
+    fn attack_table(ui: &mut egui::Ui, rows: &[AttackRow<'_>], poise: Option<f64>) {
+        TableBuilder::new(ui)
+            .vscroll(false)
+            .column(Column::auto().resizable(true)) // Attack Type
+            .column(Column::auto().resizable(true)) // Per Hit
+            .column(Column::auto().resizable(true)) // Total
+            .cell_layout(Layout::centered_and_justified(egui::Direction::TopDown))
+            .header(20.0, |mut header| {
+                header.col(|ui| {
+                    ui.label("Attack Type");
+                });
+                header.col(|ui| {
+                    ui.label("Per Hit");
+                });
+                header.col(|ui| {
+                    ui.label("Total");
+                });
+            })
+            .body(|body| {
+                body.rows(30.0, rows.len(), |mut row| {
+                    let (attack_type, cell) = &rows[row.index()];
+
+                    row.col(|ui| {
+                        ui.label(*attack_type);
+                    });
+
+                    row.col(|ui| {
+                        poise_damage_cell(ui, cell.per_hit(), cell, poise);
+                    });
+
+                    row.col(|ui| {
+                        poise_damage_cell(ui, cell.total(), cell, poise);
+                    });
+                });
+            });
+    }
+
+    impl WeaponPoiseDamage {
+        /// Cells are highlighted by whether they break `poise`, if given
+        pub fn view(&self, ui: &mut egui::Ui, poise: Option<f64>) {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::CollapsingHeader::new("One Handed / Two Handed")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        grip_table(ui, &self.grip_rows(), poise);
+                    });
+
+                for (section, rows) in self.attack_sections() {
+                    egui::CollapsingHeader::new(section).show(ui, |ui| {
+                        attack_table(ui, &rows, poise);
+                    });
+                }
             });
         }
     }
@@ -1,32 +1,60 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+#[cfg(feature = "gui")]
 use egui::Slider;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    data::Attacks,
+    data::{Attacks, WeaponPoiseDamage},
+    modifiers::{ModifierRegistry, ModifierTarget},
     static_data::{
-        BULLGOAT_MULTIPLIER, COLOSSAL_POISE_DAMAGE_MULTIPLIER, INNATE_WEAPON_POISE,
+        weapon_innate_poise, COLOSSAL_POISE_DAMAGE_MULTIPLIER, INNATE_WEAPON_POISE, MODIFIERS,
         POISE_DAMAGE_MULTIPLIER, POISE_DATA, RECOVERY_MULTIPLIER,
     },
+};
+
+#[cfg(feature = "gui")]
+use crate::{
+    selection::{SelectionBus, SelectionEvent},
     weapon_select_view::WeaponSelectView,
 };
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EquipmentViewState {
+    pub is_open: bool,
+    pub selected_weapon: Option<String>,
+    pub selected_attack: Option<Attacks>,
+    pub armor_poise: u16,
+    pub selected_modifiers: BTreeSet<String>,
+    pub is_in_recovery: bool,
+}
+
 #[derive(Default)]
 pub struct EquipmentView {
     pub is_open: bool,
-    selected_weapon: Option<String>,
+    pub selected_weapon: Option<String>,
     selected_weapon_class: Option<String>,
     selected_attack: Option<Attacks>,
-    weapon_hyperarmor: Option<f64>,
+    pub weapon_hyperarmor: Option<f64>,
     pub incoming_poise_damage_multiplier: Option<f64>,
     pub is_changed_incoming_poise_damage_multiplier: bool,
     pub armor_poise: u16,
+    #[cfg(feature = "gui")]
     is_armor_poise_changed: bool,
     pub hyperarmor: Option<f64>,
-    is_bullgoat_equipped: bool,
-    is_bullgoat_equipped_changed: bool,
-    is_in_recovery: bool,
+    pub selected_modifiers: BTreeSet<String>,
+    #[cfg(feature = "gui")]
+    is_selected_modifiers_changed: bool,
+    pub is_in_recovery: bool,
+    #[cfg(feature = "gui")]
     is_in_recovery_changed: bool,
+    #[cfg(feature = "gui")]
     is_weapon_changed: bool,
+    #[cfg(feature = "gui")]
     is_attack_changed: bool,
+    #[cfg(feature = "gui")]
+    is_loadout_changed: bool,
+    #[cfg(feature = "gui")]
     weapon_select_view: WeaponSelectView,
 }
 
@@ -41,19 +69,106 @@ impl EquipmentView {
             incoming_poise_damage_multiplier: Some(1.0),
             is_changed_incoming_poise_damage_multiplier: false,
             armor_poise: 0,
+            #[cfg(feature = "gui")]
             is_armor_poise_changed: false,
             hyperarmor: None,
-            is_bullgoat_equipped: false,
-            is_bullgoat_equipped_changed: false,
+            selected_modifiers: BTreeSet::new(),
+            #[cfg(feature = "gui")]
+            is_selected_modifiers_changed: false,
             is_in_recovery: false,
+            #[cfg(feature = "gui")]
             is_in_recovery_changed: false,
+            #[cfg(feature = "gui")]
             is_weapon_changed: false,
+            #[cfg(feature = "gui")]
             is_attack_changed: false,
+            #[cfg(feature = "gui")]
+            is_loadout_changed: false,
+            #[cfg(feature = "gui")]
             weapon_select_view: WeaponSelectView::new(),
         }
     }
 
-    pub fn show(&mut self, ui: &mut egui::Ui) {
+    /// Recomputes hyperarmor and the incoming poise damage multiplier from the loadout
+    pub fn recalculate(&mut self) {
+        match (&self.selected_weapon, &self.selected_attack) {
+            (Some(weapon), Some(attack)) => {
+                let weapon_class = POISE_DATA.get(weapon).unwrap().class.clone();
+
+                let weapon_hyperarmor = weapon_hyperarmor_from_weapon_and_attack(
+                    *INNATE_WEAPON_POISE.get(weapon).unwrap(),
+                    attack.get_hyper_armour_multiplier(),
+                    &weapon_class,
+                    weapon,
+                    attack,
+                );
+
+                let (hyperarmor, incoming_poise_damage_multiplier) =
+                    hyperarmor_and_incoming_poise_damage_multiplier(
+                        weapon_hyperarmor,
+                        &weapon_class,
+                        self.armor_poise,
+                        self.is_in_recovery,
+                        &self.selected_modifiers,
+                        &MODIFIERS,
+                    );
+
+                self.selected_weapon_class = Some(weapon_class);
+                self.weapon_hyperarmor = Some(weapon_hyperarmor);
+                self.hyperarmor = Some(hyperarmor);
+                self.incoming_poise_damage_multiplier = Some(incoming_poise_damage_multiplier);
+            }
+            _ => {
+                self.incoming_poise_damage_multiplier = Some(MODIFIERS.combined_factor(
+                    &self.selected_modifiers,
+                    ModifierTarget::IncomingPoiseDamage,
+                ));
+            }
+        }
+
+        self.is_changed_incoming_poise_damage_multiplier = true;
+    }
+
+    /// Makes the next `show` propagate the multiplier as changed, e.g. after switching loadouts
+    pub fn mark_changed(&mut self) {
+        #[cfg(feature = "gui")]
+        {
+            self.is_loadout_changed = true;
+        }
+    }
+
+    pub fn state(&self) -> EquipmentViewState {
+        EquipmentViewState {
+            is_open: self.is_open,
+            selected_weapon: self.selected_weapon.clone(),
+            selected_attack: self.selected_attack.clone(),
+            armor_poise: self.armor_poise,
+            selected_modifiers: self.selected_modifiers.clone(),
+            is_in_recovery: self.is_in_recovery,
+        }
+    }
+
+    /// Weapons and modifiers that are no longer in the data are dropped
+    pub fn restore(&mut self, state: EquipmentViewState) {
+        self.is_open = state.is_open;
+        self.selected_weapon = state
+            .selected_weapon
+            .filter(|weapon| POISE_DATA.contains_key(weapon));
+        self.selected_attack = state.selected_attack;
+        self.armor_poise = state.armor_poise;
+        self.selected_modifiers = state
+            .selected_modifiers
+            .into_iter()
+            .filter(|modifier| MODIFIERS.get(modifier).is_some())
+            .collect();
+        self.is_in_recovery = state.is_in_recovery;
+
+        self.recalculate();
+    }
+
+    /// Publishes the weapon and attack picked here, so the other views follow the loadout
+    #[cfg(feature = "gui")]
+    pub fn show(&mut self, ui: &mut egui::Ui, selection_bus: &mut SelectionBus) {
         self.is_changed_incoming_poise_damage_multiplier = false;
 
         egui::Window::new("Equipment")
@@ -81,96 +196,12 @@ impl EquipmentView {
 
                     if self.is_attack_changed {
                         log::debug!("Attack: {:?}", self.selected_attack,);
-                    }
-                });
 
-                if (self.is_attack_changed || self.is_weapon_changed)
-                    && self.selected_weapon.is_some()
-                    && self.selected_attack.is_some()
-                {
-                    let weapon = self.selected_weapon.as_ref().unwrap();
-                    let weapon_class = &POISE_DATA.get(weapon).unwrap().class;
-                    self.selected_weapon_class = Some(weapon_class.clone());
-
-                    let attack = self.selected_attack.as_ref().unwrap();
-
-                    let innate_weapon_poise = *INNATE_WEAPON_POISE.get(weapon).unwrap();
-
-                    let hyper_armor_multiplier = self
-                        .selected_attack
-                        .as_ref()
-                        .unwrap()
-                        .get_hyper_armour_multiplier();
-
-                    let weapon_hyperarmor = weapon_hyperarmor_from_weapon_and_attack(
-                        innate_weapon_poise,
-                        hyper_armor_multiplier,
-                        weapon_class,
-                        weapon,
-                        attack,
-                    );
-
-                    self.weapon_hyperarmor = Some(weapon_hyperarmor);
-                }
-
-                if (self.is_weapon_changed
-                    || self.is_attack_changed
-                    || self.is_bullgoat_equipped_changed
-                    || self.is_in_recovery_changed
-                    || self.is_armor_poise_changed)
-                    && self.selected_weapon.is_some()
-                    && self.selected_attack.is_some()
-                    && self.selected_weapon_class.is_some()
-                {
-                    let weapon_hyperarmor = self.weapon_hyperarmor.unwrap_or_default();
-                    let weapon_class = self.selected_weapon_class.as_ref().unwrap();
-
-                    match weapon_hyperarmor as u16 > 0 {
-                        true => {
-                            let base_multiplier = match weapon_class.contains("Colossal") {
-                                true => COLOSSAL_POISE_DAMAGE_MULTIPLIER,
-                                false => POISE_DAMAGE_MULTIPLIER,
-                            };
-
-                            let incoming_poise_damage_multiplier = match self.is_bullgoat_equipped {
-                                true => base_multiplier * (1.0 - BULLGOAT_MULTIPLIER),
-                                false => base_multiplier,
-                            };
-
-                            let mut hyperarmor = self.armor_poise as f64 + weapon_hyperarmor;
-
-                            if self.is_in_recovery {
-                                hyperarmor *= RECOVERY_MULTIPLIER;
-                            }
-
-                            self.incoming_poise_damage_multiplier =
-                                Some(incoming_poise_damage_multiplier);
-                            self.hyperarmor = Some(hyperarmor);
-                        }
-                        false => {
-                            self.incoming_poise_damage_multiplier = match self.is_bullgoat_equipped
-                            {
-                                true => Some(1.0 - BULLGOAT_MULTIPLIER),
-                                false => Some(1.0),
-                            };
-                            self.hyperarmor = Some(0.0);
+                        if let Some(attack) = &self.selected_attack {
+                            selection_bus.publish(SelectionEvent::AttackSelected(attack.clone()));
                         }
                     }
-
-                    self.is_changed_incoming_poise_damage_multiplier = true;
-
-                    self.is_bullgoat_equipped_changed = false;
-                    self.is_in_recovery_changed = false;
-                    self.is_weapon_changed = false;
-                    self.is_attack_changed = false;
-                } else if self.is_bullgoat_equipped_changed {
-                    self.incoming_poise_damage_multiplier = match self.is_bullgoat_equipped {
-                        true => Some(1.0 - BULLGOAT_MULTIPLIER),
-                        false => Some(1.0),
-                    };
-
-                    self.is_changed_incoming_poise_damage_multiplier = true;
-                }
+                });
 
                 ui.label(format!(
                     "Weapon Hyperarmor: {}",
@@ -198,11 +229,22 @@ impl EquipmentView {
                     self.hyperarmor.map(|x| x.to_string()).unwrap_or_default()
                 ));
 
-                ui.checkbox(&mut self.is_bullgoat_equipped, "Bullgoat Equipped")
-                    .clicked()
-                    .then(|| {
-                        self.is_bullgoat_equipped_changed = true;
-                    });
+                ui.collapsing("Talismans & Buffs", |ui| {
+                    for modifier in MODIFIERS.iter() {
+                        let mut is_selected = self.selected_modifiers.contains(&modifier.name);
+
+                        ui.checkbox(&mut is_selected, &modifier.name)
+                            .on_hover_text(format!("{} x{}", modifier.target, modifier.factor))
+                            .clicked()
+                            .then(|| {
+                                match is_selected {
+                                    true => self.selected_modifiers.insert(modifier.name.clone()),
+                                    false => self.selected_modifiers.remove(&modifier.name),
+                                };
+                                self.is_selected_modifiers_changed = true;
+                            });
+                    }
+                });
 
                 ui.checkbox(&mut self.is_in_recovery, "In Recovery")
                     .clicked()
@@ -229,11 +271,87 @@ impl EquipmentView {
                     self.is_weapon_changed = true;
 
                     log::debug!("Selected Weapon: {:?} ", selected_weapon,);
+
+                    selection_bus.publish(SelectionEvent::WeaponSelected(selected_weapon));
                 }
             });
+
+        if self.is_weapon_changed
+            || self.is_attack_changed
+            || self.is_selected_modifiers_changed
+            || self.is_in_recovery_changed
+            || self.is_armor_poise_changed
+            || self.is_loadout_changed
+        {
+            self.recalculate();
+
+            self.is_selected_modifiers_changed = false;
+            self.is_in_recovery_changed = false;
+            self.is_armor_poise_changed = false;
+            self.is_weapon_changed = false;
+            self.is_attack_changed = false;
+            self.is_loadout_changed = false;
+        }
     }
 }
 
+pub fn hyperarmor_multiplier(
+    is_in_recovery: bool,
+    selected_modifiers: &BTreeSet<String>,
+    modifiers: &ModifierRegistry,
+) -> f64 {
+    let recovery_multiplier = match is_in_recovery {
+        true => RECOVERY_MULTIPLIER,
+        false => 1.0,
+    };
+
+    recovery_multiplier * modifiers.combined_factor(selected_modifiers, ModifierTarget::Hyperarmor)
+}
+
+pub fn hyperarmor_and_incoming_poise_damage_multiplier(
+    weapon_hyperarmor: f64,
+    weapon_class: &str,
+    armor_poise: u16,
+    is_in_recovery: bool,
+    selected_modifiers: &BTreeSet<String>,
+    modifiers: &ModifierRegistry,
+) -> (f64, f64) {
+    let incoming_modifier =
+        modifiers.combined_factor(selected_modifiers, ModifierTarget::IncomingPoiseDamage);
+
+    match weapon_hyperarmor as u16 > 0 {
+        true => {
+            let base_multiplier = match weapon_class.contains("Colossal") {
+                true => COLOSSAL_POISE_DAMAGE_MULTIPLIER,
+                false => POISE_DAMAGE_MULTIPLIER,
+            };
+
+            let hyperarmor = (armor_poise as f64 + weapon_hyperarmor)
+                * hyperarmor_multiplier(is_in_recovery, selected_modifiers, modifiers);
+
+            (hyperarmor, base_multiplier * incoming_modifier)
+        }
+        false => (0.0, incoming_modifier),
+    }
+}
+
+/// Poise that has to be broken, hyperarmor if the attack has any, armor poise otherwise
+pub fn effective_poise(hyperarmor: f64, armor_poise: u16) -> f64 {
+    match hyperarmor > 0.0 {
+        true => hyperarmor,
+        false => armor_poise as f64,
+    }
+}
+
+/// Hits of `poise_damage` needed to break `poise`, ignoring poise regeneration between hits
+pub fn hits_to_stagger(poise_damage: f64, poise: f64) -> Option<u32> {
+    if poise_damage <= 0.0 {
+        return None;
+    }
+
+    Some(((poise / poise_damage).ceil() as u32).max(1))
+}
+
 pub fn weapon_hyperarmor_from_weapon_and_attack(
     innate_weapon_poise: u16,
     hyper_armor_multiplier: f64,
@@ -266,6 +384,49 @@ pub fn weapon_hyperarmor_from_weapon_and_attack(
     }
 }
 
+/// Poise the enemy has to lose and the multiplier applied to outgoing poise damage
+pub fn enemy_poise_and_multiplier(
+    poise_data: &BTreeMap<String, WeaponPoiseDamage>,
+    enemy_weapon: &Option<String>,
+    enemy_attack: &Option<Attacks>,
+    enemy_armor_poise: u16,
+    is_enemy_in_recovery: bool,
+    selected_modifiers: &BTreeSet<String>,
+) -> (f64, f64) {
+    let (enemy_hyperarmor, enemy_incoming_poise_damage_multiplier) =
+        match (enemy_weapon, enemy_attack) {
+            (Some(weapon), Some(attack)) => {
+                let weapon_class = &poise_data[weapon].class;
+
+                let weapon_hyperarmor = weapon_hyperarmor_from_weapon_and_attack(
+                    weapon_innate_poise(weapon, weapon_class),
+                    attack.get_hyper_armour_multiplier(),
+                    weapon_class,
+                    weapon,
+                    attack,
+                );
+
+                hyperarmor_and_incoming_poise_damage_multiplier(
+                    weapon_hyperarmor,
+                    weapon_class,
+                    enemy_armor_poise,
+                    is_enemy_in_recovery,
+                    &BTreeSet::new(),
+                    &MODIFIERS,
+                )
+            }
+            _ => (0.0, 1.0),
+        };
+
+    let outgoing_modifier =
+        MODIFIERS.combined_factor(selected_modifiers, ModifierTarget::OutgoingPoiseDamage);
+
+    (
+        effective_poise(enemy_hyperarmor, enemy_armor_poise),
+        enemy_incoming_poise_damage_multiplier * outgoing_modifier,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -310,4 +471,60 @@ mod tests {
 
         assert_eq!(weapon_hyperarmor as u16, 39);
     }
+
+    #[test]
+    fn test_hyperarmor_modifier() {
+        let modifiers = ModifierRegistry::from_reader(
+            "name,target,factor,stacking_group
+Bull-Goat's Talisman,IncomingPoiseDamage,0.75,
+Stance Buff,Hyperarmor,1.2,
+"
+            .as_bytes(),
+        );
+
+        let selected_modifiers = ["Bull-Goat's Talisman", "Stance Buff"]
+            .into_iter()
+            .map(String::from)
+            .collect::<BTreeSet<String>>();
+
+        assert_eq!(
+            hyperarmor_multiplier(true, &selected_modifiers, &modifiers),
+            RECOVERY_MULTIPLIER * 1.2
+        );
+
+        let (hyperarmor, incoming_poise_damage_multiplier) =
+            hyperarmor_and_incoming_poise_damage_multiplier(
+                39.0,
+                "Greatsword",
+                51,
+                false,
+                &selected_modifiers,
+                &modifiers,
+            );
+
+        assert_eq!(hyperarmor, (51.0 + 39.0) * 1.2);
+        assert_eq!(
+            incoming_poise_damage_multiplier,
+            POISE_DAMAGE_MULTIPLIER * 0.75
+        );
+
+        let (hyperarmor, _) = hyperarmor_and_incoming_poise_damage_multiplier(
+            39.0,
+            "Greatsword",
+            51,
+            false,
+            &BTreeSet::new(),
+            &modifiers,
+        );
+
+        assert_eq!(hyperarmor, 51.0 + 39.0);
+    }
+
+    #[test]
+    fn test_hits_to_stagger() {
+        assert_eq!(hits_to_stagger(50.0, 51.0), Some(2));
+        assert_eq!(hits_to_stagger(51.0, 51.0), Some(1));
+        assert_eq!(hits_to_stagger(10.0, 0.0), Some(1));
+        assert_eq!(hits_to_stagger(0.0, 51.0), None);
+    }
 }
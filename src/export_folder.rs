@@ -0,0 +1,70 @@
+use std::path::{Path, PathBuf};
+
+/// `<file stem>.<extension>` in `directory`, numbered like `<file stem> (2).<extension>` if taken
+pub fn unused_path(directory: &Path, file_stem: &str, extension: &str) -> PathBuf {
+    (1..)
+        .map(|number| match number {
+            1 => directory.join(format!("{}.{}", file_stem, extension)),
+            number => directory.join(format!("{} ({}).{}", file_stem, number, extension)),
+        })
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+/// Folder a view exports to and the outcome of its last export, shared by plot and table exports
+#[derive(Default)]
+pub struct ExportFolder {
+    /// Empty for the working directory
+    directory: String,
+    last_export: Option<String>,
+}
+
+impl ExportFolder {
+    pub fn show_folder(&mut self, ui: &mut egui::Ui) {
+        ui.label("Folder");
+        ui.add(
+            egui::TextEdit::singleline(&mut self.directory)
+                .hint_text("Working directory")
+                .desired_width(160.0),
+        );
+    }
+
+    pub fn show_last_export(&self, ui: &mut egui::Ui) {
+        if let Some(last_export) = &self.last_export {
+            ui.label(last_export);
+        }
+    }
+
+    pub fn set_last_export(&mut self, last_export: String) {
+        self.last_export = Some(last_export);
+    }
+
+    /// Absolute path of a new file in the folder, existing files are never overwritten
+    pub fn new_path(&self, file_stem: &str, extension: &str) -> PathBuf {
+        let path = unused_path(Path::new(&self.directory), file_stem, extension);
+
+        std::path::absolute(&path).unwrap_or(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unused_path() {
+        let directory = std::env::temp_dir().join("hyperarmor_inspector_unused_path");
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let path = unused_path(&directory, "coverage", "svg");
+        assert_eq!(path, directory.join("coverage.svg"));
+
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(
+            unused_path(&directory, "coverage", "svg"),
+            directory.join("coverage (2).svg")
+        );
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
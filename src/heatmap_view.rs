@@ -0,0 +1,389 @@
+use std::collections::BTreeMap;
+
+use egui::{epaint::TextShape, Color32, FontId, Rect, Sense};
+
+use crate::{
+    data::{ApplyMultiplier, Attacks, PoiseDamageValues},
+    equipment_view::effective_poise,
+    one_attack_plot_view::AxisLabels,
+    plot_export::{AxisTicks, Chart, ChartItem, ExportControls},
+    static_data::{POISE_DATA, WEAPON_CLASSES},
+};
+
+const CELL_SIZE: f32 = 14.0;
+const LABEL_WIDTH: f32 = 220.0;
+const HEADER_HEIGHT: f32 = 150.0;
+
+const TANKED_COLOR: (Color32, Color32) = (
+    Color32::from_rgb(30, 90, 30),
+    Color32::from_rgb(60, 220, 60),
+);
+const STAGGERED_COLOR: (Color32, Color32) = (
+    Color32::from_rgb(120, 60, 30),
+    Color32::from_rgb(240, 30, 30),
+);
+
+/// Green when `poise_damage` is tanked, red when it staggers, brighter the larger the margin
+pub fn heatmap_cell_color(poise_damage: Option<f64>, poise: f64) -> Color32 {
+    let Some(poise_damage) = poise_damage else {
+        return Color32::from_gray(40);
+    };
+
+    let relative_margin = match poise > 0.0 {
+        true => (poise_damage - poise) / poise,
+        false => 1.0,
+    };
+
+    match poise_damage >= poise {
+        true => STAGGERED_COLOR
+            .0
+            .lerp_to_gamma(STAGGERED_COLOR.1, relative_margin.clamp(0.0, 1.0) as f32),
+        false => TANKED_COLOR
+            .0
+            .lerp_to_gamma(TANKED_COLOR.1, (-relative_margin).clamp(0.0, 1.0) as f32),
+    }
+}
+
+/// Total of the hits scaled by `multiplier`, truncated per hit like in the plots
+fn scaled_poise_damage(
+    poise_damage_values: &Option<PoiseDamageValues>,
+    multiplier: f64,
+) -> Option<f64> {
+    poise_damage_values
+        .as_ref()
+        .map(|poise_damage_values| poise_damage_values.apply_multiplier(multiplier).total() as f64)
+}
+
+enum HeatmapRow {
+    Class(String),
+    Weapon(String),
+}
+
+/// Rows go down from y = 0, class rows are left empty like on screen
+fn heatmap_chart(
+    attacks: &[Attacks],
+    rows: &[HeatmapRow],
+    poise_damage_by_weapon: &BTreeMap<String, Vec<Option<PoiseDamageValues>>>,
+    multiplier: f64,
+    poise: f64,
+) -> Chart {
+    let mut chart = Chart::new(
+        format!(
+            "Poise: {:.1}, Incoming Poise Damage Multiplier: {}",
+            poise, multiplier
+        ),
+        "",
+        "",
+    );
+
+    chart.width = (LABEL_WIDTH + 40.0 + attacks.len() as f32 * CELL_SIZE) as u32;
+    chart.height = (HEADER_HEIGHT + 80.0 + rows.len() as f32 * CELL_SIZE) as u32;
+
+    chart.x_ticks = AxisTicks::Labels(
+        attacks
+            .iter()
+            .enumerate()
+            .map(|(column, attack)| (column as i64, attack.to_string()))
+            .collect(),
+    );
+
+    let mut row_labels = AxisLabels::new();
+
+    for (row, heatmap_row) in rows.iter().enumerate() {
+        let y = -(row as f64);
+
+        match heatmap_row {
+            HeatmapRow::Class(weapon_class) => {
+                row_labels.insert(-(row as i64), format!("— {} —", weapon_class));
+            }
+            HeatmapRow::Weapon(weapon) => {
+                row_labels.insert(-(row as i64), weapon.clone());
+
+                for (column, poise_damage) in poise_damage_by_weapon[weapon].iter().enumerate() {
+                    chart.items.push(ChartItem::Cell {
+                        center: [column as f64, y],
+                        color: heatmap_cell_color(
+                            scaled_poise_damage(poise_damage, multiplier),
+                            poise,
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    chart.y_ticks = AxisTicks::Labels(row_labels);
+
+    chart
+}
+
+pub struct HeatmapView {
+    pub is_open: bool,
+    attacks: Vec<Attacks>,
+    rows: Vec<HeatmapRow>,
+    /// Unscaled hits, scaled every frame so the totals truncate like everywhere else
+    poise_damage_by_weapon: BTreeMap<String, Vec<Option<PoiseDamageValues>>>,
+    export_controls: ExportControls,
+}
+
+impl Default for HeatmapView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HeatmapView {
+    pub fn new() -> Self {
+        let poise_damage_by_weapon = POISE_DATA
+            .iter()
+            .map(|(weapon, weapon_poise_damage)| {
+                let poise_damage = Attacks::all()
+                    .iter()
+                    .map(|attack| {
+                        weapon_poise_damage
+                            .get_poise_damage_by_attack(attack)
+                            .filter(|poise_damage_values| !poise_damage_values.is_empty())
+                            .cloned()
+                    })
+                    .collect::<Vec<_>>();
+
+                (weapon.clone(), poise_damage)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        // attacks no weapon has are left out
+        let attack_indices = (0..Attacks::all().len())
+            .filter(|i| {
+                poise_damage_by_weapon
+                    .values()
+                    .any(|poise_damage| poise_damage[*i].is_some())
+            })
+            .collect::<Vec<_>>();
+
+        let attacks = attack_indices
+            .iter()
+            .map(|i| Attacks::all()[*i].clone())
+            .collect();
+
+        let poise_damage_by_weapon = poise_damage_by_weapon
+            .into_iter()
+            .map(|(weapon, poise_damage)| {
+                (
+                    weapon,
+                    attack_indices
+                        .iter()
+                        .map(|i| poise_damage[*i].clone())
+                        .collect(),
+                )
+            })
+            .collect();
+
+        let rows = WEAPON_CLASSES
+            .iter()
+            .flat_map(|(weapon_class, weapons)| {
+                std::iter::once(HeatmapRow::Class(weapon_class.clone()))
+                    .chain(weapons.iter().cloned().map(HeatmapRow::Weapon))
+            })
+            .collect();
+
+        Self {
+            is_open: false,
+            attacks,
+            rows,
+            poise_damage_by_weapon,
+            export_controls: ExportControls::default(),
+        }
+    }
+
+    pub fn chart(&self, incoming_poise_damage_multiplier: f64, poise: f64) -> Chart {
+        heatmap_chart(
+            &self.attacks,
+            &self.rows,
+            &self.poise_damage_by_weapon,
+            incoming_poise_damage_multiplier,
+            poise,
+        )
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        incoming_poise_damage_multiplier: &Option<f64>,
+        hyperarmor: &Option<f64>,
+        armor_poise: &u16,
+    ) {
+        const INITIAL_WINDOW_SIZE: [f32; 2] = [800.0, 600.0];
+
+        egui::Window::new("Heatmap")
+            .id("Heatmap Window".into())
+            .resizable(true)
+            .title_bar(true)
+            .default_size(INITIAL_WINDOW_SIZE)
+            .open(&mut self.is_open)
+            .show(ui.ctx(), |ui| {
+                let multiplier = incoming_poise_damage_multiplier.unwrap_or(1.0);
+                let poise = effective_poise(hyperarmor.unwrap_or_default(), *armor_poise);
+
+                ui.label(format!(
+                    "Poise: {:.1}, Incoming Poise Damage Multiplier: {}",
+                    poise, multiplier
+                ));
+
+                if let Some(format) = self.export_controls.show(ui) {
+                    let chart = heatmap_chart(
+                        &self.attacks,
+                        &self.rows,
+                        &self.poise_damage_by_weapon,
+                        multiplier,
+                        poise,
+                    );
+
+                    self.export_controls.export(format, &[("heatmap", chart)]);
+                }
+
+                ui.separator();
+
+                egui::ScrollArea::both().show_viewport(ui, |ui, viewport| {
+                    let size = egui::vec2(
+                        LABEL_WIDTH + self.attacks.len() as f32 * CELL_SIZE,
+                        HEADER_HEIGHT + self.rows.len() as f32 * CELL_SIZE,
+                    );
+
+                    let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+
+                    let painter = ui.painter();
+                    let text_color = ui.visuals().text_color();
+                    let font_id = FontId::proportional(10.0);
+
+                    for (column, attack) in self.attacks.iter().enumerate() {
+                        let galley =
+                            painter.layout_no_wrap(attack.to_string(), font_id.clone(), text_color);
+
+                        let pos = rect.min
+                            + egui::vec2(
+                                LABEL_WIDTH + (column as f32 + 0.5) * CELL_SIZE
+                                    - galley.size().y / 2.0,
+                                HEADER_HEIGHT - 4.0,
+                            );
+
+                        painter.add(
+                            TextShape::new(pos, galley, text_color)
+                                .with_angle(-std::f32::consts::FRAC_PI_2),
+                        );
+                    }
+
+                    let first_row = ((viewport.min.y - HEADER_HEIGHT) / CELL_SIZE)
+                        .floor()
+                        .max(0.0) as usize;
+                    let last_row = (((viewport.max.y - HEADER_HEIGHT) / CELL_SIZE)
+                        .ceil()
+                        .max(0.0) as usize)
+                        .min(self.rows.len());
+
+                    for row in first_row..last_row {
+                        let top = rect.min.y + HEADER_HEIGHT + row as f32 * CELL_SIZE;
+
+                        match &self.rows[row] {
+                            HeatmapRow::Class(weapon_class) => {
+                                painter.text(
+                                    egui::pos2(rect.min.x, top),
+                                    egui::Align2::LEFT_TOP,
+                                    weapon_class,
+                                    FontId::proportional(12.0),
+                                    ui.visuals().strong_text_color(),
+                                );
+                            }
+                            HeatmapRow::Weapon(weapon) => {
+                                painter.text(
+                                    egui::pos2(rect.min.x + 8.0, top),
+                                    egui::Align2::LEFT_TOP,
+                                    weapon,
+                                    font_id.clone(),
+                                    text_color,
+                                );
+
+                                for (column, poise_damage) in
+                                    self.poise_damage_by_weapon[weapon].iter().enumerate()
+                                {
+                                    let cell = Rect::from_min_size(
+                                        egui::pos2(
+                                            rect.min.x + LABEL_WIDTH + column as f32 * CELL_SIZE,
+                                            top,
+                                        ),
+                                        egui::vec2(CELL_SIZE - 1.0, CELL_SIZE - 1.0),
+                                    );
+
+                                    painter.rect_filled(
+                                        cell,
+                                        0.0,
+                                        heatmap_cell_color(
+                                            scaled_poise_damage(poise_damage, multiplier),
+                                            poise,
+                                        ),
+                                    );
+                                }
+                            }
+                        }
+                    }
+
+                    let Some(hover_pos) = response.hover_pos() else {
+                        return;
+                    };
+
+                    let local_pos = hover_pos - rect.min;
+
+                    if local_pos.x < LABEL_WIDTH || local_pos.y < HEADER_HEIGHT {
+                        return;
+                    }
+
+                    let row = ((local_pos.y - HEADER_HEIGHT) / CELL_SIZE) as usize;
+                    let column = ((local_pos.x - LABEL_WIDTH) / CELL_SIZE) as usize;
+
+                    if let (Some(HeatmapRow::Weapon(weapon)), Some(attack)) =
+                        (self.rows.get(row), self.attacks.get(column))
+                    {
+                        let Some(poise_damage) = scaled_poise_damage(
+                            &self.poise_damage_by_weapon[weapon][column],
+                            multiplier,
+                        ) else {
+                            return;
+                        };
+
+                        response.on_hover_ui_at_pointer(|ui| {
+                            ui.label(format!("{} - {}", weapon, attack));
+                            ui.label(format!("Poise Damage: {:.1}", poise_damage));
+                            ui.label(format!("Margin: {:+.1}", poise_damage - poise));
+                        });
+                    }
+                });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heatmap_cell_color() {
+        assert_eq!(heatmap_cell_color(None, 50.0), Color32::from_gray(40));
+
+        let staggered = heatmap_cell_color(Some(100.0), 50.0);
+        assert_eq!(staggered, STAGGERED_COLOR.1);
+
+        let barely_staggered = heatmap_cell_color(Some(50.0), 50.0);
+        assert_eq!(barely_staggered, STAGGERED_COLOR.0);
+
+        let tanked = heatmap_cell_color(Some(25.0), 50.0);
+        assert!(tanked.g() > tanked.r());
+        assert_ne!(tanked, TANKED_COLOR.0);
+    }
+
+    #[test]
+    fn test_scaled_poise_damage() {
+        let poise_damage_values = Some(PoiseDamageValues(vec![30, 30]));
+
+        assert_eq!(scaled_poise_damage(&poise_damage_values, 0.65), Some(38.0));
+        assert_eq!(scaled_poise_damage(&None, 0.65), None);
+    }
+}
@@ -1,10 +1,44 @@
 #![feature(lazy_cell)]
 
+#[cfg(feature = "server")]
+pub mod api;
+#[cfg(feature = "gui")]
+pub mod app_state;
+#[cfg(feature = "gui")]
+pub mod build_planner_view;
+#[cfg(feature = "gui")]
+pub mod comparison_view;
+#[cfg(feature = "gui")]
+pub mod coverage_view;
 pub mod data;
+#[cfg(feature = "download")]
 pub mod download;
 pub mod equipment_view;
+#[cfg(feature = "gui")]
+pub mod export_folder;
+#[cfg(feature = "gui")]
+pub mod heatmap_view;
+pub mod loadout_code;
+#[cfg(feature = "gui")]
+pub mod loadouts_view;
 pub mod macros;
+pub mod matchup;
+pub mod modifiers;
+#[cfg(feature = "gui")]
 pub mod one_attack_plot_view;
+#[cfg(feature = "gui")]
+pub mod outgoing_poise_view;
+#[cfg(feature = "gui")]
+pub mod plot_export;
+#[cfg(feature = "gui")]
 pub mod poise_data_view;
+pub mod query;
+pub mod selection;
+#[cfg(feature = "gui")]
+pub mod staggers_me_view;
 pub mod static_data;
+pub mod table_export;
+#[cfg(feature = "gui")]
+pub mod two_attack_scatter_view;
+#[cfg(feature = "gui")]
 pub mod weapon_select_view;
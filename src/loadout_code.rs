@@ -0,0 +1,317 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Display, Formatter},
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+use crate::{
+    data::{Attacks, WeaponPoiseDamage},
+    equipment_view::EquipmentViewState,
+    modifiers::ModifierRegistry,
+    static_data::{MODIFIERS, POISE_DATA, RULESET_VERSION},
+};
+
+const PREFIX: &str = "HAI";
+
+/// Bumped whenever the layout of the encoded fields changes
+pub const LOADOUT_CODE_VERSION: u32 = 1;
+
+const FIELD_SEPARATOR: char = '|';
+const MODIFIER_SEPARATOR: char = ';';
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadoutCodeError {
+    InvalidFormat,
+    UnsupportedVersion(u32),
+    RulesetMismatch { code: u32, current: u32 },
+    UnknownWeapon(String),
+    UnknownAttack(String),
+    MissingAttack { weapon: String, attack: Attacks },
+    UnknownModifier(String),
+}
+
+impl Display for LoadoutCodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadoutCodeError::InvalidFormat => write!(f, "Not a loadout code"),
+            LoadoutCodeError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported loadout code version {}", version)
+            }
+            LoadoutCodeError::RulesetMismatch { code, current } => write!(
+                f,
+                "Loadout code uses ruleset {}, the current ruleset is {}",
+                code, current
+            ),
+            LoadoutCodeError::UnknownWeapon(weapon) => write!(f, "Unknown weapon {}", weapon),
+            LoadoutCodeError::UnknownAttack(attack) => write!(f, "Unknown attack {}", attack),
+            LoadoutCodeError::MissingAttack { weapon, attack } => {
+                write!(f, "{} has no poise damage for {}", weapon, attack)
+            }
+            LoadoutCodeError::UnknownModifier(modifier) => {
+                write!(f, "Unknown modifier {}", modifier)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadoutCodeError {}
+
+// Example
+// HAI1-MXxDbGF5bW9yZXxUd29IYW5kZWRSMUNoYWluKDApfDUxfDB8
+// decodes to "1|Claymore|TwoHandedR1Chain(0)|51|0|", the fields being
+// ruleset version, weapon, attack, armor poise, in recovery and the modifiers
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadoutCode {
+    pub ruleset_version: u32,
+    pub selected_weapon: Option<String>,
+    pub selected_attack: Option<Attacks>,
+    pub armor_poise: u16,
+    pub is_in_recovery: bool,
+    pub selected_modifiers: BTreeSet<String>,
+}
+
+impl LoadoutCode {
+    pub fn from_state(state: &EquipmentViewState) -> Self {
+        Self {
+            ruleset_version: RULESET_VERSION,
+            selected_weapon: state.selected_weapon.clone(),
+            selected_attack: state.selected_attack.clone(),
+            armor_poise: state.armor_poise,
+            is_in_recovery: state.is_in_recovery,
+            selected_modifiers: state.selected_modifiers.clone(),
+        }
+    }
+
+    pub fn into_state(self) -> EquipmentViewState {
+        EquipmentViewState {
+            is_open: true,
+            selected_weapon: self.selected_weapon,
+            selected_attack: self.selected_attack,
+            armor_poise: self.armor_poise,
+            selected_modifiers: self.selected_modifiers,
+            is_in_recovery: self.is_in_recovery,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let fields = [
+            self.ruleset_version.to_string(),
+            self.selected_weapon.clone().unwrap_or_default(),
+            self.selected_attack
+                .as_ref()
+                .map(|attack| format!("{:?}", attack))
+                .unwrap_or_default(),
+            self.armor_poise.to_string(),
+            (self.is_in_recovery as u8).to_string(),
+            self.selected_modifiers
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(&MODIFIER_SEPARATOR.to_string()),
+        ];
+
+        format!(
+            "{}{}-{}",
+            PREFIX,
+            LOADOUT_CODE_VERSION,
+            URL_SAFE_NO_PAD.encode(fields.join(&FIELD_SEPARATOR.to_string()))
+        )
+    }
+
+    /// Only checks the format, see `validate` for checking against the data
+    pub fn parse(code: &str) -> Result<Self, LoadoutCodeError> {
+        let (version, payload) = code
+            .trim()
+            .strip_prefix(PREFIX)
+            .and_then(|code| code.split_once('-'))
+            .ok_or(LoadoutCodeError::InvalidFormat)?;
+
+        let version = version
+            .parse::<u32>()
+            .map_err(|_| LoadoutCodeError::InvalidFormat)?;
+
+        if version != LOADOUT_CODE_VERSION {
+            return Err(LoadoutCodeError::UnsupportedVersion(version));
+        }
+
+        let payload = URL_SAFE_NO_PAD
+            .decode(payload)
+            .ok()
+            .and_then(|payload| String::from_utf8(payload).ok())
+            .ok_or(LoadoutCodeError::InvalidFormat)?;
+
+        let fields = payload.split(FIELD_SEPARATOR).collect::<Vec<_>>();
+
+        let [ruleset_version, weapon, attack, armor_poise, is_in_recovery, modifiers] = fields[..]
+        else {
+            return Err(LoadoutCodeError::InvalidFormat);
+        };
+
+        let selected_attack = match attack.is_empty() {
+            true => None,
+            false => Some(
+                Attacks::all()
+                    .into_iter()
+                    .find(|known_attack| format!("{:?}", known_attack) == attack)
+                    .ok_or_else(|| LoadoutCodeError::UnknownAttack(attack.to_string()))?,
+            ),
+        };
+
+        Ok(Self {
+            ruleset_version: ruleset_version
+                .parse()
+                .map_err(|_| LoadoutCodeError::InvalidFormat)?,
+            selected_weapon: (!weapon.is_empty()).then(|| weapon.to_string()),
+            selected_attack,
+            armor_poise: armor_poise
+                .parse()
+                .map_err(|_| LoadoutCodeError::InvalidFormat)?,
+            is_in_recovery: match is_in_recovery {
+                "0" => false,
+                "1" => true,
+                _ => return Err(LoadoutCodeError::InvalidFormat),
+            },
+            selected_modifiers: modifiers
+                .split(MODIFIER_SEPARATOR)
+                .filter(|modifier| !modifier.is_empty())
+                .map(|modifier| modifier.to_string())
+                .collect(),
+        })
+    }
+
+    pub fn validate(
+        &self,
+        poise_data: &BTreeMap<String, WeaponPoiseDamage>,
+        modifiers: &ModifierRegistry,
+    ) -> Result<(), LoadoutCodeError> {
+        if self.ruleset_version != RULESET_VERSION {
+            return Err(LoadoutCodeError::RulesetMismatch {
+                code: self.ruleset_version,
+                current: RULESET_VERSION,
+            });
+        }
+
+        if let Some(weapon) = &self.selected_weapon {
+            let weapon_poise_damage = poise_data
+                .get(weapon)
+                .ok_or_else(|| LoadoutCodeError::UnknownWeapon(weapon.clone()))?;
+
+            if let Some(attack) = &self.selected_attack {
+                if weapon_poise_damage
+                    .get_poise_damage_by_attack(attack)
+                    .is_none_or(|values| values.is_empty())
+                {
+                    return Err(LoadoutCodeError::MissingAttack {
+                        weapon: weapon.clone(),
+                        attack: attack.clone(),
+                    });
+                }
+            }
+        }
+
+        match self
+            .selected_modifiers
+            .iter()
+            .find(|modifier| modifiers.get(modifier).is_none())
+        {
+            Some(modifier) => Err(LoadoutCodeError::UnknownModifier(modifier.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Parses and validates against the current data
+    pub fn decode(code: &str) -> Result<Self, LoadoutCodeError> {
+        let loadout_code = Self::parse(code)?;
+
+        loadout_code.validate(&POISE_DATA, &MODIFIERS)?;
+
+        Ok(loadout_code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loadout_code() -> LoadoutCode {
+        LoadoutCode {
+            ruleset_version: RULESET_VERSION,
+            selected_weapon: Some("Claymore".to_string()),
+            selected_attack: Some(Attacks::TwoHandedR1Chain(0)),
+            armor_poise: 51,
+            is_in_recovery: false,
+            selected_modifiers: BTreeSet::from(["Bull-Goat's Talisman".to_string()]),
+        }
+    }
+
+    #[test]
+    fn test_encode_and_parse() {
+        let code = loadout_code().encode();
+
+        assert!(code.starts_with("HAI1-"));
+        assert_eq!(LoadoutCode::parse(&code), Ok(loadout_code()));
+
+        let empty = LoadoutCode {
+            selected_weapon: None,
+            selected_attack: None,
+            selected_modifiers: BTreeSet::new(),
+            ..loadout_code()
+        };
+
+        assert_eq!(LoadoutCode::parse(&empty.encode()), Ok(empty));
+
+        assert_eq!(
+            LoadoutCode::parse("Claymore"),
+            Err(LoadoutCodeError::InvalidFormat)
+        );
+        assert_eq!(
+            LoadoutCode::parse("HAI2-MQ"),
+            Err(LoadoutCodeError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let claymore = WeaponPoiseDamage::test_weapon("Claymore", "Greatsword")
+            .with_hits(|w| &mut w.two_handed.r1.chain.one, vec![63]);
+
+        let poise_data = BTreeMap::from([("Claymore".to_string(), claymore)]);
+        let modifiers = ModifierRegistry::from_reader(
+            "name,target,factor,stacking_group\nBull-Goat's Talisman,IncomingPoiseDamage,0.75,\n"
+                .as_bytes(),
+        );
+
+        assert_eq!(loadout_code().validate(&poise_data, &modifiers), Ok(()));
+
+        let zweihander = LoadoutCode {
+            selected_weapon: Some("Zweihander".to_string()),
+            ..loadout_code()
+        };
+
+        assert_eq!(
+            zweihander.validate(&poise_data, &modifiers),
+            Err(LoadoutCodeError::UnknownWeapon("Zweihander".to_string()))
+        );
+
+        let running = LoadoutCode {
+            selected_attack: Some(Attacks::TwoHandedR1Running),
+            ..loadout_code()
+        };
+
+        assert!(matches!(
+            running.validate(&poise_data, &modifiers),
+            Err(LoadoutCodeError::MissingAttack { .. })
+        ));
+
+        let old_ruleset = LoadoutCode {
+            ruleset_version: 0,
+            ..loadout_code()
+        };
+
+        assert!(matches!(
+            old_ruleset.validate(&poise_data, &modifiers),
+            Err(LoadoutCodeError::RulesetMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    equipment_view::{effective_poise, EquipmentView, EquipmentViewState},
+    loadout_code::LoadoutCode,
+    selection::{SelectionBus, SelectionEvent},
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoadoutState {
+    pub name: String,
+    pub equipment_view: EquipmentViewState,
+}
+
+pub struct Loadout {
+    pub name: String,
+    pub equipment_view: EquipmentView,
+}
+
+pub struct LoadoutsView {
+    pub loadouts: Vec<Loadout>,
+    pub active: usize,
+    /// Loadouts drawn on top of the active one in the plot
+    pub overlaid: BTreeSet<usize>,
+    renaming: Option<usize>,
+    rename_buffer: String,
+    import_code: String,
+    import_error: Option<String>,
+}
+
+impl Default for LoadoutsView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoadoutsView {
+    pub fn new() -> Self {
+        Self {
+            loadouts: vec![Loadout {
+                name: "Loadout 1".to_string(),
+                equipment_view: EquipmentView::new(),
+            }],
+            active: 0,
+            overlaid: BTreeSet::new(),
+            renaming: None,
+            rename_buffer: String::new(),
+            import_code: String::new(),
+            import_error: None,
+        }
+    }
+
+    pub fn active(&self) -> &EquipmentView {
+        &self.loadouts[self.active].equipment_view
+    }
+
+    pub fn active_mut(&mut self) -> &mut EquipmentView {
+        &mut self.loadouts[self.active].equipment_view
+    }
+
+    fn unique_name(&self, base: &str) -> String {
+        (1..)
+            .map(|i| match i {
+                1 => base.to_string(),
+                i => format!("{} ({})", base, i),
+            })
+            .find(|name| self.loadouts.iter().all(|loadout| &loadout.name != name))
+            .unwrap()
+    }
+
+    pub fn switch_to(&mut self, index: usize) {
+        if index == self.active || index >= self.loadouts.len() {
+            return;
+        }
+
+        let is_open = self.active().is_open;
+
+        self.active = index;
+        self.overlaid.remove(&index);
+
+        let equipment_view = self.active_mut();
+        equipment_view.is_open = is_open;
+        equipment_view.mark_changed();
+    }
+
+    pub fn create(&mut self) {
+        let name = self.unique_name(&format!("Loadout {}", self.loadouts.len() + 1));
+
+        self.loadouts.push(Loadout {
+            name,
+            equipment_view: EquipmentView::new(),
+        });
+
+        self.switch_to(self.loadouts.len() - 1);
+    }
+
+    pub fn duplicate(&mut self, index: usize) {
+        let Some(loadout) = self.loadouts.get(index) else {
+            return;
+        };
+
+        let name = self.unique_name(&loadout.name);
+
+        let mut equipment_view = EquipmentView::new();
+        equipment_view.restore(loadout.equipment_view.state());
+
+        self.loadouts.push(Loadout {
+            name,
+            equipment_view,
+        });
+
+        self.switch_to(self.loadouts.len() - 1);
+    }
+
+    /// Adds the decoded loadout as a new loadout named after its weapon
+    pub fn import(&mut self, loadout_code: LoadoutCode) {
+        let name = self.unique_name(
+            loadout_code
+                .selected_weapon
+                .as_deref()
+                .unwrap_or("Imported Loadout"),
+        );
+
+        let mut equipment_view = EquipmentView::new();
+        equipment_view.restore(loadout_code.into_state());
+
+        self.loadouts.push(Loadout {
+            name,
+            equipment_view,
+        });
+
+        self.switch_to(self.loadouts.len() - 1);
+    }
+
+    /// Empty and already taken names are ignored
+    pub fn rename(&mut self, index: usize, name: &str) {
+        let name = name.trim();
+
+        if name.is_empty() || self.loadouts.iter().any(|loadout| loadout.name == name) {
+            return;
+        }
+
+        if let Some(loadout) = self.loadouts.get_mut(index) {
+            loadout.name = name.to_string();
+        }
+    }
+
+    /// The last loadout can't be deleted
+    pub fn delete(&mut self, index: usize) {
+        if self.loadouts.len() <= 1 || index >= self.loadouts.len() {
+            return;
+        }
+
+        let is_open = self.active().is_open;
+
+        self.loadouts.remove(index);
+        self.renaming = None;
+
+        self.overlaid = self
+            .overlaid
+            .iter()
+            .filter(|i| **i != index)
+            .map(|i| if *i > index { i - 1 } else { *i })
+            .collect();
+
+        if self.active >= index && self.active > 0 {
+            self.active -= 1;
+        }
+
+        // the loadout taking over can't overlay itself
+        self.overlaid.remove(&self.active);
+
+        let equipment_view = self.active_mut();
+        equipment_view.is_open = is_open;
+        equipment_view.mark_changed();
+    }
+
+    /// Poise of every overlaid loadout, scaled to the incoming poise damage of the active one
+    pub fn overlay_lines(&self) -> Vec<(String, f64)> {
+        let active_multiplier = self
+            .active()
+            .incoming_poise_damage_multiplier
+            .unwrap_or(1.0);
+
+        self.overlaid
+            .iter()
+            .filter_map(|i| self.loadouts.get(*i))
+            .map(|loadout| {
+                let equipment_view = &loadout.equipment_view;
+
+                let poise = effective_poise(
+                    equipment_view.hyperarmor.unwrap_or_default(),
+                    equipment_view.armor_poise,
+                );
+
+                let multiplier = equipment_view
+                    .incoming_poise_damage_multiplier
+                    .unwrap_or(1.0);
+
+                (loadout.name.clone(), poise / multiplier * active_multiplier)
+            })
+            .collect()
+    }
+
+    pub fn state(&self) -> Vec<LoadoutState> {
+        self.loadouts
+            .iter()
+            .map(|loadout| LoadoutState {
+                name: loadout.name.clone(),
+                equipment_view: loadout.equipment_view.state(),
+            })
+            .collect()
+    }
+
+    pub fn restore(&mut self, state: Vec<LoadoutState>, active: usize) {
+        if state.is_empty() {
+            return;
+        }
+
+        self.loadouts = state
+            .into_iter()
+            .map(|loadout_state| {
+                let mut equipment_view = EquipmentView::new();
+                equipment_view.restore(loadout_state.equipment_view);
+
+                Loadout {
+                    name: loadout_state.name,
+                    equipment_view,
+                }
+            })
+            .collect();
+
+        self.active = active.min(self.loadouts.len() - 1);
+        self.overlaid.clear();
+        self.renaming = None;
+    }
+
+    pub fn on_selection_event(&mut self, event: &SelectionEvent) {
+        if let SelectionEvent::LoadoutSelected(index) = event {
+            self.switch_to(*index);
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, selection_bus: &mut SelectionBus) {
+        let mut switch_to = None;
+        let mut duplicate = None;
+        let mut delete = None;
+        let mut renamed = None;
+        let mut is_create_requested = false;
+        let mut is_import_requested = false;
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Loadouts: ");
+
+            for (i, loadout) in self.loadouts.iter().enumerate() {
+                if self.renaming == Some(i) {
+                    let response = ui.text_edit_singleline(&mut self.rename_buffer);
+
+                    response.request_focus();
+
+                    if response.lost_focus() {
+                        renamed = Some(i);
+                    }
+
+                    continue;
+                }
+
+                let response = ui.selectable_label(i == self.active, &loadout.name);
+
+                if response.clicked() {
+                    switch_to = Some(i);
+                }
+
+                if response.double_clicked() {
+                    self.renaming = Some(i);
+                    self.rename_buffer = loadout.name.clone();
+                }
+
+                response.context_menu(|ui| {
+                    ui.button("Rename").clicked().then(|| {
+                        self.renaming = Some(i);
+                        self.rename_buffer = loadout.name.clone();
+                        ui.close_menu();
+                    });
+
+                    ui.button("Copy Code").clicked().then(|| {
+                        let code = LoadoutCode::from_state(&loadout.equipment_view.state());
+                        ui.output_mut(|output| output.copied_text = code.encode());
+                        ui.close_menu();
+                    });
+
+                    ui.button("Duplicate").clicked().then(|| {
+                        duplicate = Some(i);
+                        ui.close_menu();
+                    });
+
+                    ui.add_enabled(self.loadouts.len() > 1, egui::Button::new("Delete"))
+                        .clicked()
+                        .then(|| {
+                            delete = Some(i);
+                            ui.close_menu();
+                        });
+                });
+
+                if i != self.active {
+                    let mut is_overlaid = self.overlaid.contains(&i);
+
+                    ui.checkbox(&mut is_overlaid, "")
+                        .on_hover_text("Overlay in the plot")
+                        .clicked()
+                        .then(|| match is_overlaid {
+                            true => self.overlaid.insert(i),
+                            false => self.overlaid.remove(&i),
+                        });
+                }
+            }
+
+            ui.button("+")
+                .on_hover_text("New Loadout")
+                .clicked()
+                .then(|| {
+                    is_create_requested = true;
+                });
+
+            ui.separator();
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.import_code)
+                    .hint_text("Paste loadout code")
+                    .desired_width(200.0),
+            );
+
+            ui.button("Import").clicked().then(|| {
+                is_import_requested = true;
+            });
+
+            if let Some(import_error) = &self.import_error {
+                ui.colored_label(ui.visuals().error_fg_color, import_error);
+            }
+        });
+
+        let previous_active = self.active;
+
+        if is_import_requested {
+            match LoadoutCode::decode(&self.import_code) {
+                Ok(loadout_code) => {
+                    self.import(loadout_code);
+                    self.import_code.clear();
+                    self.import_error = None;
+                }
+                Err(error) => {
+                    log::warn!("Error importing loadout code: {}", error);
+                    self.import_error = Some(error.to_string());
+                }
+            }
+        }
+
+        if let Some(i) = renamed {
+            let name = std::mem::take(&mut self.rename_buffer);
+            self.rename(i, &name);
+            self.renaming = None;
+        }
+
+        if let Some(i) = switch_to {
+            selection_bus.publish(SelectionEvent::LoadoutSelected(i));
+        }
+
+        if let Some(i) = duplicate {
+            self.duplicate(i);
+        }
+
+        if let Some(i) = delete {
+            self.delete(i);
+        }
+
+        if is_create_requested {
+            self.create();
+        }
+
+        if self.active != previous_active {
+            selection_bus.publish(SelectionEvent::LoadoutSelected(self.active));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loadouts() {
+        let mut loadouts_view = LoadoutsView::new();
+
+        loadouts_view.active_mut().armor_poise = 51;
+
+        loadouts_view.duplicate(0);
+        loadouts_view.create();
+
+        let names = loadouts_view
+            .loadouts
+            .iter()
+            .map(|loadout| loadout.name.clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec!["Loadout 1", "Loadout 1 (2)", "Loadout 3"]);
+        assert_eq!(loadouts_view.active, 2);
+        assert_eq!(loadouts_view.loadouts[1].equipment_view.armor_poise, 51);
+
+        loadouts_view.rename(2, "Loadout 1");
+        assert_eq!(loadouts_view.loadouts[2].name, "Loadout 3");
+
+        loadouts_view.rename(2, "Heavy");
+        assert_eq!(loadouts_view.loadouts[2].name, "Heavy");
+
+        loadouts_view.overlaid.insert(1);
+        loadouts_view.delete(0);
+
+        assert_eq!(loadouts_view.loadouts.len(), 2);
+        assert_eq!(loadouts_view.active, 1);
+        assert_eq!(loadouts_view.overlaid, BTreeSet::from([0]));
+
+        loadouts_view.delete(0);
+        loadouts_view.delete(0);
+
+        assert_eq!(loadouts_view.loadouts.len(), 1);
+        assert_eq!(loadouts_view.loadouts[0].name, "Heavy");
+
+        loadouts_view.create();
+        loadouts_view.create();
+        loadouts_view.overlaid.insert(1);
+        loadouts_view.delete(2);
+
+        assert_eq!(loadouts_view.active, 1);
+        assert!(loadouts_view.overlaid.is_empty());
+    }
+}
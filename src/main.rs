@@ -37,28 +37,109 @@ fn main() -> eframe::Result {
 #[derive(Default)]
 struct App {
     poise_data_view: poise_data_view::PoiseDataView,
-    equipment_view: equipment_view::EquipmentView,
+    loadouts_view: loadouts_view::LoadoutsView,
     one_attack_plot_view: one_attack_plot_view::OneAttackPlotView,
+    build_planner_view: build_planner_view::BuildPlannerView,
+    outgoing_poise_view: outgoing_poise_view::OutgoingPoiseView,
+    staggers_me_view: staggers_me_view::StaggersMeView,
+    heatmap_view: heatmap_view::HeatmapView,
+    comparison_view: comparison_view::ComparisonView,
+    coverage_view: coverage_view::CoverageView,
+    two_attack_scatter_view: two_attack_scatter_view::TwoAttackScatterView,
+    selection_bus: selection::SelectionBus,
 }
 
 impl App {
-    fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = Self::with_default_views();
+
+        if let Some(state) = cc.storage.and_then(app_state::AppState::load) {
+            log::info!("Restoring saved state");
+            app.restore(state);
+        }
+
+        app
+    }
+
+    fn with_default_views() -> Self {
         Self {
             poise_data_view: poise_data_view::PoiseDataView::new(),
-            equipment_view: equipment_view::EquipmentView::new(),
+            loadouts_view: loadouts_view::LoadoutsView::new(),
             one_attack_plot_view: one_attack_plot_view::OneAttackPlotView::new(),
+            build_planner_view: build_planner_view::BuildPlannerView::new(),
+            outgoing_poise_view: outgoing_poise_view::OutgoingPoiseView::new(),
+            staggers_me_view: staggers_me_view::StaggersMeView::new(),
+            heatmap_view: heatmap_view::HeatmapView::new(),
+            comparison_view: comparison_view::ComparisonView::new(),
+            coverage_view: coverage_view::CoverageView::new(),
+            two_attack_scatter_view: two_attack_scatter_view::TwoAttackScatterView::new(),
+            selection_bus: selection::SelectionBus::new(),
+        }
+    }
+
+    fn other_windows(&mut self) -> [(&str, &mut bool); 7] {
+        [
+            ("Build Planner", &mut self.build_planner_view.is_open),
+            ("Outgoing Poise", &mut self.outgoing_poise_view.is_open),
+            ("What Staggers Me", &mut self.staggers_me_view.is_open),
+            ("Heatmap", &mut self.heatmap_view.is_open),
+            ("Weapon Comparison", &mut self.comparison_view.is_open),
+            ("Coverage", &mut self.coverage_view.is_open),
+            (
+                "Two Attack Scatter",
+                &mut self.two_attack_scatter_view.is_open,
+            ),
+        ]
+    }
+
+    fn state(&mut self) -> app_state::AppState {
+        app_state::AppState {
+            version: app_state::APP_STATE_VERSION,
+            loadouts: self.loadouts_view.state(),
+            active_loadout: self.loadouts_view.active,
+            poise_data_view: self.poise_data_view.state(),
+            one_attack_plot_view: self.one_attack_plot_view.state(),
+            open_windows: self
+                .other_windows()
+                .into_iter()
+                .map(|(name, is_open)| (name.to_string(), *is_open))
+                .collect(),
+        }
+    }
+
+    fn restore(&mut self, state: app_state::AppState) {
+        self.loadouts_view
+            .restore(state.loadouts, state.active_loadout);
+        self.poise_data_view.restore(state.poise_data_view);
+        self.one_attack_plot_view.restore(
+            state.one_attack_plot_view,
+            &self.loadouts_view.active().incoming_poise_damage_multiplier,
+        );
+
+        for (name, is_open) in self.other_windows() {
+            if let Some(was_open) = state.open_windows.get(name) {
+                *is_open = *was_open;
+            }
         }
     }
 }
 
 impl eframe::App for App {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.state().save(storage);
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let mut is_reset_requested = false;
+
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
-                ui.selectable_label(self.equipment_view.is_open, "Equipment")
+                let equipment_view = self.loadouts_view.active_mut();
+
+                ui.selectable_label(equipment_view.is_open, "Equipment")
                     .clicked()
                     .then(|| {
-                        self.equipment_view.is_open = !self.equipment_view.is_open;
+                        equipment_view.is_open = !equipment_view.is_open;
                     });
 
                 ui.selectable_label(self.poise_data_view.is_open, "Poise Data")
@@ -72,30 +153,139 @@ impl eframe::App for App {
                     .then(|| {
                         self.one_attack_plot_view.is_open = !self.one_attack_plot_view.is_open;
                     });
+
+                ui.selectable_label(self.build_planner_view.is_open, "Build Planner")
+                    .clicked()
+                    .then(|| {
+                        self.build_planner_view.is_open = !self.build_planner_view.is_open;
+                    });
+
+                ui.selectable_label(self.outgoing_poise_view.is_open, "Outgoing Poise")
+                    .clicked()
+                    .then(|| {
+                        self.outgoing_poise_view.is_open = !self.outgoing_poise_view.is_open;
+                    });
+
+                ui.selectable_label(self.staggers_me_view.is_open, "What Staggers Me")
+                    .clicked()
+                    .then(|| {
+                        self.staggers_me_view.is_open = !self.staggers_me_view.is_open;
+                    });
+
+                ui.selectable_label(self.heatmap_view.is_open, "Heatmap")
+                    .clicked()
+                    .then(|| {
+                        self.heatmap_view.is_open = !self.heatmap_view.is_open;
+                    });
+
+                ui.selectable_label(self.comparison_view.is_open, "Weapon Comparison")
+                    .clicked()
+                    .then(|| {
+                        self.comparison_view.is_open = !self.comparison_view.is_open;
+                    });
+
+                ui.selectable_label(self.coverage_view.is_open, "Coverage")
+                    .clicked()
+                    .then(|| {
+                        self.coverage_view.is_open = !self.coverage_view.is_open;
+                    });
+
+                ui.selectable_label(self.two_attack_scatter_view.is_open, "Two Attack Scatter")
+                    .clicked()
+                    .then(|| {
+                        self.two_attack_scatter_view.is_open =
+                            !self.two_attack_scatter_view.is_open;
+                    });
+
+                ui.separator();
+
+                ui.button("Reset to Defaults").clicked().then(|| {
+                    is_reset_requested = true;
+                });
             });
         });
 
+        egui::TopBottomPanel::top("loadouts_panel").show(ctx, |ui| {
+            self.loadouts_view.show(ui, &mut self.selection_bus);
+        });
+
+        if is_reset_requested {
+            log::info!("Resetting to defaults");
+            *self = Self::with_default_views();
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
-            if self.equipment_view.is_open {
-                self.equipment_view.show(ui);
-            }
+            // always shown so switching loadouts propagates even with the window closed
+            self.loadouts_view
+                .active_mut()
+                .show(ui, &mut self.selection_bus);
+
+            let equipment_view = self.loadouts_view.active();
 
             if self.poise_data_view.is_open {
                 self.poise_data_view
-                    .show(ui, &self.equipment_view.incoming_poise_damage_multiplier);
+                    .show(ui, equipment_view, &mut self.selection_bus);
             }
 
             if self.one_attack_plot_view.is_open {
                 self.one_attack_plot_view.show(
                     ui,
-                    &self
-                        .equipment_view
-                        .is_changed_incoming_poise_damage_multiplier,
-                    &self.equipment_view.incoming_poise_damage_multiplier,
-                    &self.equipment_view.hyperarmor,
-                    &self.equipment_view.armor_poise,
+                    equipment_view,
+                    &self.loadouts_view.overlay_lines(),
+                    &mut self.selection_bus,
+                );
+            }
+
+            if self.build_planner_view.is_open {
+                self.build_planner_view
+                    .show(ui, equipment_view, &mut self.selection_bus);
+            }
+
+            if self.outgoing_poise_view.is_open {
+                self.outgoing_poise_view.show(ui, equipment_view);
+            }
+
+            if self.staggers_me_view.is_open {
+                self.staggers_me_view.show(ui, equipment_view);
+            }
+
+            if self.heatmap_view.is_open {
+                self.heatmap_view.show(
+                    ui,
+                    &equipment_view.incoming_poise_damage_multiplier,
+                    &equipment_view.hyperarmor,
+                    &equipment_view.armor_poise,
+                );
+            }
+
+            if self.comparison_view.is_open {
+                self.comparison_view.show(ui, equipment_view);
+            }
+
+            if self.coverage_view.is_open {
+                self.coverage_view
+                    .show(ui, equipment_view, &mut self.selection_bus);
+            }
+
+            if self.two_attack_scatter_view.is_open {
+                self.two_attack_scatter_view.show(
+                    ui,
+                    equipment_view,
+                    self.one_attack_plot_view.selected_weapon_classes(),
+                    &mut self.selection_bus,
                 );
             }
         });
+
+        for event in self.selection_bus.take_events() {
+            self.loadouts_view.on_selection_event(&event);
+            self.poise_data_view.on_selection_event(&event);
+            self.one_attack_plot_view.on_selection_event(&event);
+            self.build_planner_view.on_selection_event(&event);
+            self.coverage_view.on_selection_event(&event);
+            self.two_attack_scatter_view.on_selection_event(&event);
+
+            ctx.request_repaint();
+        }
     }
 }
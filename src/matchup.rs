@@ -0,0 +1,424 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Display, Formatter},
+    io::{Read, Write},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{Attacks, PoiseDamageValues, ThresholdStatus, WeaponPoiseDamage},
+    equipment_view::{
+        effective_poise, enemy_poise_and_multiplier,
+        hyperarmor_and_incoming_poise_damage_multiplier, weapon_hyperarmor_from_weapon_and_attack,
+    },
+    query::{find_attack, find_modifier, find_weapon, QueryError},
+    static_data::{weapon_innate_poise, MODIFIERS},
+};
+
+/// Separates the modifiers in the `modifiers` column, like in loadout codes
+const MODIFIER_SEPARATOR: char = ';';
+
+/// A row of the input CSV, the attacks are thrown at the same time
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+pub struct Matchup {
+    pub my_weapon: String,
+    pub my_attack: String,
+    pub armor_poise: u16,
+    #[serde(default)]
+    pub modifiers: String,
+    pub their_weapon: String,
+    pub their_attack: String,
+    #[serde(default)]
+    pub their_armor_poise: u16,
+}
+
+impl Matchup {
+    pub fn modifiers(&self) -> Vec<String> {
+        self.modifiers
+            .split(MODIFIER_SEPARATOR)
+            .map(|modifier| modifier.trim())
+            .filter(|modifier| !modifier.is_empty())
+            .map(|modifier| modifier.to_string())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Trade {
+    /// Only they are staggered
+    Win,
+    /// Only I am staggered
+    Loss,
+    BothStaggered,
+    NeitherStaggered,
+}
+
+impl Trade {
+    pub fn new(is_my_attack_staggering: bool, is_their_attack_staggering: bool) -> Self {
+        match (is_my_attack_staggering, is_their_attack_staggering) {
+            (true, false) => Trade::Win,
+            (false, true) => Trade::Loss,
+            (true, true) => Trade::BothStaggered,
+            (false, false) => Trade::NeitherStaggered,
+        }
+    }
+}
+
+impl Display for Trade {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Trade::Win => write!(f, "Win"),
+            Trade::Loss => write!(f, "Loss"),
+            Trade::BothStaggered => write!(f, "Both Staggered"),
+            Trade::NeitherStaggered => write!(f, "Neither Staggered"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MatchupResult {
+    pub hyperarmor: f64,
+    pub incoming_poise_damage: f64,
+    pub poise: f64,
+    pub outcome: ThresholdStatus,
+    /// Incoming poise damage minus my poise, staggered from 0 on
+    pub margin: f64,
+    pub outgoing_poise_damage: f64,
+    pub their_poise: f64,
+    pub their_outcome: ThresholdStatus,
+    pub trade: Trade,
+}
+
+/// Poise damage of `attack` scaled by `multiplier`, truncated per hit like in the plots
+fn scaled_poise_damage(
+    poise_data: &BTreeMap<String, WeaponPoiseDamage>,
+    weapon: &str,
+    attack: &Attacks,
+    multiplier: f64,
+) -> Result<PoiseDamageValues, QueryError> {
+    poise_data[weapon]
+        .get_poise_damage_by_attack_w_multiplier(attack, multiplier)
+        .filter(|poise_damage_values| !poise_damage_values.is_empty())
+        .ok_or_else(|| QueryError::WeaponWithoutAttack(weapon.to_string(), attack.clone()))
+}
+
+/// Both attacks of `matchup` hit at the same time, neither of us is in recovery
+pub fn evaluate(
+    poise_data: &BTreeMap<String, WeaponPoiseDamage>,
+    matchup: &Matchup,
+) -> Result<MatchupResult, QueryError> {
+    let my_weapon = find_weapon(poise_data, &matchup.my_weapon)?;
+    let my_attack = find_attack(&matchup.my_attack)?;
+    let their_weapon = find_weapon(poise_data, &matchup.their_weapon)?;
+    let their_attack = find_attack(&matchup.their_attack)?;
+
+    let selected_modifiers = matchup
+        .modifiers()
+        .iter()
+        .map(|modifier| find_modifier(modifier).map(|modifier| modifier.name.clone()))
+        .collect::<Result<BTreeSet<_>, _>>()?;
+
+    let my_weapon_class = &poise_data[my_weapon].class;
+
+    let weapon_hyperarmor = weapon_hyperarmor_from_weapon_and_attack(
+        weapon_innate_poise(my_weapon, my_weapon_class),
+        my_attack.get_hyper_armour_multiplier(),
+        my_weapon_class,
+        my_weapon,
+        &my_attack,
+    );
+
+    let (hyperarmor, incoming_multiplier) = hyperarmor_and_incoming_poise_damage_multiplier(
+        weapon_hyperarmor,
+        my_weapon_class,
+        matchup.armor_poise,
+        false,
+        &selected_modifiers,
+        &MODIFIERS,
+    );
+    let poise = effective_poise(hyperarmor, matchup.armor_poise);
+
+    let incoming_poise_damage =
+        scaled_poise_damage(poise_data, their_weapon, &their_attack, incoming_multiplier)?;
+
+    let (their_poise, outgoing_multiplier) = enemy_poise_and_multiplier(
+        poise_data,
+        &Some(their_weapon.clone()),
+        &Some(their_attack),
+        matchup.their_armor_poise,
+        false,
+        &selected_modifiers,
+    );
+
+    let outgoing_poise_damage =
+        scaled_poise_damage(poise_data, my_weapon, &my_attack, outgoing_multiplier)?;
+
+    // the poise damage values are never empty, so there always is a status
+    let outcome = incoming_poise_damage
+        .threshold_status(poise)
+        .unwrap_or(ThresholdStatus::Tanked);
+    let their_outcome = outgoing_poise_damage
+        .threshold_status(their_poise)
+        .unwrap_or(ThresholdStatus::Tanked);
+
+    let incoming_poise_damage = incoming_poise_damage.total() as f64;
+
+    Ok(MatchupResult {
+        hyperarmor,
+        incoming_poise_damage,
+        poise,
+        outcome,
+        margin: incoming_poise_damage - poise,
+        outgoing_poise_damage: outgoing_poise_damage.total() as f64,
+        their_poise,
+        their_outcome,
+        trade: Trade::new(
+            their_outcome != ThresholdStatus::Tanked,
+            outcome != ThresholdStatus::Tanked,
+        ),
+    })
+}
+
+/// Columns of the result CSV after the input columns
+const RESULT_COLUMNS: [&str; 10] = [
+    "hyperarmor",
+    "incoming_poise_damage",
+    "poise",
+    "outcome",
+    "margin",
+    "outgoing_poise_damage",
+    "their_poise",
+    "their_outcome",
+    "trade",
+    "error",
+];
+
+/// The input columns followed by the results, or empty results and the error
+fn result_record(matchup: &Matchup, result: &Result<MatchupResult, QueryError>) -> Vec<String> {
+    let mut record = vec![
+        matchup.my_weapon.clone(),
+        matchup.my_attack.clone(),
+        matchup.armor_poise.to_string(),
+        matchup.modifiers.clone(),
+        matchup.their_weapon.clone(),
+        matchup.their_attack.clone(),
+        matchup.their_armor_poise.to_string(),
+    ];
+
+    match result {
+        Ok(result) => record.extend([
+            format!("{:.1}", result.hyperarmor),
+            format!("{:.1}", result.incoming_poise_damage),
+            format!("{:.1}", result.poise),
+            result.outcome.to_string(),
+            format!("{:+.1}", result.margin),
+            format!("{:.1}", result.outgoing_poise_damage),
+            format!("{:.1}", result.their_poise),
+            result.their_outcome.to_string(),
+            result.trade.to_string(),
+            String::new(),
+        ]),
+        Err(error) => {
+            record.resize(record.len() + RESULT_COLUMNS.len() - 1, String::new());
+            record.push(error.to_string());
+        }
+    }
+
+    record
+}
+
+/// Evaluates every row of `reader` with `evaluate` and writes the results to `writer`
+///
+/// Rows that fail are kept with the error, unreadable rows stop the batch.
+/// Returns the number of rows and how many of them failed.
+pub fn evaluate_csv(
+    reader: impl Read,
+    writer: impl Write,
+    evaluate: impl Fn(&Matchup) -> Result<MatchupResult, QueryError>,
+) -> Result<(usize, usize), csv::Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .comment(Some(b'#'))
+        .from_reader(reader);
+    let mut writer = csv::Writer::from_writer(writer);
+
+    writer.write_record(
+        [
+            "my_weapon",
+            "my_attack",
+            "armor_poise",
+            "modifiers",
+            "their_weapon",
+            "their_attack",
+            "their_armor_poise",
+        ]
+        .into_iter()
+        .chain(RESULT_COLUMNS),
+    )?;
+
+    let mut rows = 0;
+    let mut failed = 0;
+
+    for matchup in reader.deserialize::<Matchup>() {
+        let matchup = matchup?;
+        let result = evaluate(&matchup);
+
+        if let Err(error) = &result {
+            log::warn!(
+                "Failed to evaluate {} against {}: {}",
+                matchup.my_weapon,
+                matchup.their_weapon,
+                error
+            );
+            failed += 1;
+        }
+
+        writer.write_record(result_record(&matchup, &result))?;
+        rows += 1;
+    }
+
+    writer.flush()?;
+
+    Ok((rows, failed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modifiers() {
+        let matchup = Matchup {
+            modifiers: "Bull-Goat's Talisman; Stonebarb Cracked Tear;".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            matchup.modifiers(),
+            vec!["Bull-Goat's Talisman", "Stonebarb Cracked Tear"]
+        );
+        assert!(Matchup::default().modifiers().is_empty());
+    }
+
+    #[test]
+    fn test_trade() {
+        assert_eq!(Trade::new(true, false), Trade::Win);
+        assert_eq!(Trade::new(false, true), Trade::Loss);
+        assert_eq!(Trade::new(true, true).to_string(), "Both Staggered");
+    }
+
+    fn poise_data() -> BTreeMap<String, WeaponPoiseDamage> {
+        let claymore = WeaponPoiseDamage::test_weapon("Claymore", "Greatsword")
+            .with_hits(|w| &mut w.two_handed.r1.chain.one, vec![60]);
+
+        let dagger = WeaponPoiseDamage::test_weapon("Dagger", "Dagger")
+            .with_hits(|w| &mut w.one_handed.r1.chain.one, vec![30]);
+
+        BTreeMap::from([
+            ("Claymore".to_string(), claymore),
+            ("Dagger".to_string(), dagger),
+        ])
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let poise_data = poise_data();
+
+        let claymore = |armor_poise| ("Claymore", "TwoHandedR1Chain(0)", armor_poise);
+        let dagger = |armor_poise| ("Dagger", "OneHandedR1Chain(0)", armor_poise);
+
+        let matchup = |me: (&str, &str, u16), them: (&str, &str, u16)| Matchup {
+            my_weapon: me.0.to_string(),
+            my_attack: me.1.to_string(),
+            armor_poise: me.2,
+            their_weapon: them.0.to_string(),
+            their_attack: them.1.to_string(),
+            their_armor_poise: them.2,
+            ..Default::default()
+        };
+
+        // Greatsword innate poise 59 on top of 51 armor poise, the dagger hit is scaled by 0.65
+        let win = evaluate(&poise_data, &matchup(claymore(51), dagger(20))).unwrap();
+
+        assert_eq!(win.hyperarmor, 110.0);
+        assert_eq!(win.poise, 110.0);
+        assert_eq!(win.incoming_poise_damage, 19.0);
+        assert_eq!(win.margin, 19.0 - 110.0);
+        assert_eq!(win.outcome, ThresholdStatus::Tanked);
+        assert_eq!(win.their_poise, 20.0);
+        assert_eq!(win.outgoing_poise_damage, 60.0);
+        assert_eq!(win.their_outcome, ThresholdStatus::Staggered);
+        assert_eq!(win.trade, Trade::Win);
+
+        let loss = evaluate(&poise_data, &matchup(dagger(20), claymore(51))).unwrap();
+
+        assert_eq!(loss.hyperarmor, 0.0);
+        assert_eq!(loss.poise, 20.0);
+        assert_eq!(loss.incoming_poise_damage, 60.0);
+        assert_eq!(loss.margin, 40.0);
+        assert_eq!(loss.outcome, ThresholdStatus::Staggered);
+        assert_eq!(loss.their_poise, 110.0);
+        assert_eq!(loss.outgoing_poise_damage, 19.0);
+        assert_eq!(loss.their_outcome, ThresholdStatus::Tanked);
+        assert_eq!(loss.trade, Trade::Loss);
+
+        assert_eq!(
+            evaluate(
+                &poise_data,
+                &matchup(dagger(20), ("Dagger", "Shieldpoke", 0))
+            ),
+            Err(QueryError::WeaponWithoutAttack(
+                "Dagger".to_string(),
+                Attacks::Shieldpoke
+            ))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_csv() {
+        let input = "\
+my_weapon,my_attack,armor_poise,modifiers,their_weapon,their_attack
+Claymore,TwoHandedR1Chain(0),51,,Zweihander,TwoHandedR2Chain(0)
+Claymore,Nope,51,,Zweihander,TwoHandedR2Chain(0)
+";
+
+        let evaluate = |matchup: &Matchup| match matchup.my_attack.as_str() {
+            "Nope" => Err(QueryError::UnknownAttack("Nope".to_string())),
+            _ => Ok(MatchupResult {
+                hyperarmor: 0.0,
+                incoming_poise_damage: 60.0,
+                poise: 51.0,
+                outcome: ThresholdStatus::Staggered,
+                margin: 9.0,
+                outgoing_poise_damage: 40.0,
+                their_poise: 51.0,
+                their_outcome: ThresholdStatus::Tanked,
+                trade: Trade::Loss,
+            }),
+        };
+
+        let mut output = Vec::new();
+
+        assert_eq!(
+            evaluate_csv(input.as_bytes(), &mut output, evaluate).unwrap(),
+            (2, 1)
+        );
+
+        let output = String::from_utf8(output).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            lines[0],
+            "my_weapon,my_attack,armor_poise,modifiers,their_weapon,their_attack,their_armor_poise,\
+             hyperarmor,incoming_poise_damage,poise,outcome,margin,outgoing_poise_damage,their_poise,\
+             their_outcome,trade,error"
+        );
+        assert_eq!(
+            lines[1],
+            "Claymore,TwoHandedR1Chain(0),51,,Zweihander,TwoHandedR2Chain(0),0,\
+             0.0,60.0,51.0,Staggered,+9.0,40.0,51.0,Tanked,Loss,"
+        );
+        assert!(lines[2].ends_with(",,,,,,,,,Unknown attack Nope"));
+    }
+}
@@ -0,0 +1,211 @@
+use std::{collections::BTreeSet, io::Read, ops::Deref};
+
+use strum::{Display, EnumIter, EnumString};
+
+pub const MODIFIERS_FILE: &str = "modifiers.csv";
+
+const DEFAULT_MODIFIERS: &str = include_str!("../modifiers.csv");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, EnumString, Display)]
+pub enum ModifierTarget {
+    IncomingPoiseDamage,
+    OutgoingPoiseDamage,
+    Hyperarmor,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Modifier {
+    pub name: String,
+    pub target: ModifierTarget,
+    pub factor: f64,
+    /// Modifiers in the same group don't stack, only the strongest one applies
+    pub stacking_group: Option<String>,
+}
+
+impl Modifier {
+    fn strength(&self) -> f64 {
+        self.factor.ln().abs()
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ModifierRegistry(pub Vec<Modifier>);
+
+impl Deref for ModifierRegistry {
+    type Target = Vec<Modifier>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl ModifierRegistry {
+    pub fn from_reader<R: Read>(reader: R) -> Self {
+        // Flexible, so a short row is skipped below instead of failing the whole file
+        let mut rdr = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+
+        let mut modifiers = Vec::new();
+
+        for record in rdr.records() {
+            let record = match record {
+                Ok(record) => record,
+                Err(error) => {
+                    log::warn!("Error reading modifier with error: {}. Skipping", error);
+                    continue;
+                }
+            };
+
+            let (Some(name), Some(target), Some(factor)) =
+                (record.get(0), record.get(1), record.get(2))
+            else {
+                log::warn!(
+                    "Modifier {:?} needs a name, target and factor. Skipping",
+                    record
+                );
+                continue;
+            };
+
+            let name = name.trim().to_string();
+            let target = target.trim();
+            let factor = factor.trim();
+            let stacking_group = record.get(3).unwrap_or_default().trim();
+
+            let target = match target.parse::<ModifierTarget>() {
+                Ok(target) => target,
+                Err(error) => {
+                    log::warn!(
+                        "Error parsing target {} of modifier {} with error: {}. Skipping",
+                        target,
+                        name,
+                        error
+                    );
+                    continue;
+                }
+            };
+
+            let factor = match factor.parse::<f64>() {
+                Ok(factor) if factor > 0.0 => factor,
+                _ => {
+                    log::warn!("Invalid factor {} of modifier {}. Skipping", factor, name);
+                    continue;
+                }
+            };
+
+            modifiers.push(Modifier {
+                name,
+                target,
+                factor,
+                stacking_group: match stacking_group.is_empty() {
+                    true => None,
+                    false => Some(stacking_group.to_string()),
+                },
+            });
+        }
+
+        Self(modifiers)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Modifier> {
+        self.iter().find(|modifier| modifier.name == name)
+    }
+
+    /// Combined factor of all `selected_modifiers` with the given target, respecting stacking groups
+    pub fn combined_factor(
+        &self,
+        selected_modifiers: &BTreeSet<String>,
+        target: ModifierTarget,
+    ) -> f64 {
+        let mut strongest_per_group = Vec::<&Modifier>::new();
+        let mut factor = 1.0;
+
+        for modifier in self
+            .iter()
+            .filter(|modifier| modifier.target == target)
+            .filter(|modifier| selected_modifiers.contains(&modifier.name))
+        {
+            let Some(stacking_group) = &modifier.stacking_group else {
+                factor *= modifier.factor;
+                continue;
+            };
+
+            match strongest_per_group
+                .iter_mut()
+                .find(|strongest| strongest.stacking_group.as_ref() == Some(stacking_group))
+            {
+                Some(strongest) if strongest.strength() < modifier.strength() => {
+                    *strongest = modifier;
+                }
+                Some(_) => {}
+                None => strongest_per_group.push(modifier),
+            }
+        }
+
+        strongest_per_group
+            .iter()
+            .fold(factor, |factor, modifier| factor * modifier.factor)
+    }
+}
+
+pub fn load_modifiers() -> ModifierRegistry {
+    match std::fs::File::open(MODIFIERS_FILE) {
+        Ok(file) => ModifierRegistry::from_reader(file),
+        Err(_) => {
+            log::info!("No {} found, using the default modifiers", MODIFIERS_FILE);
+            ModifierRegistry::from_reader(DEFAULT_MODIFIERS.as_bytes())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combined_factor() {
+        let registry = ModifierRegistry::from_reader(
+            "name,target,factor,stacking_group
+Bull-Goat's Talisman,IncomingPoiseDamage,0.75,
+Stonebarb Cracked Tear,OutgoingPoiseDamage,1.3,Physick
+Weaker Tear,OutgoingPoiseDamage,1.1,Physick
+Buff,OutgoingPoiseDamage,1.5,
+Broken,Unknown,1.5,
+Short,OutgoingPoiseDamage
+"
+            .as_bytes(),
+        );
+
+        assert_eq!(registry.len(), 4);
+
+        let selected = [
+            "Bull-Goat's Talisman",
+            "Stonebarb Cracked Tear",
+            "Weaker Tear",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect::<BTreeSet<String>>();
+
+        assert_eq!(
+            registry.combined_factor(&selected, ModifierTarget::IncomingPoiseDamage),
+            0.75
+        );
+        assert_eq!(
+            registry.combined_factor(&selected, ModifierTarget::OutgoingPoiseDamage),
+            1.3
+        );
+        assert_eq!(
+            registry.combined_factor(&selected, ModifierTarget::Hyperarmor),
+            1.0
+        );
+
+        let selected = ["Weaker Tear", "Buff"]
+            .into_iter()
+            .map(String::from)
+            .collect::<BTreeSet<String>>();
+
+        assert_eq!(
+            registry.combined_factor(&selected, ModifierTarget::OutgoingPoiseDamage),
+            1.1 * 1.5
+        );
+    }
+}
@@ -1,59 +1,553 @@
 use std::collections::{BTreeMap, HashMap};
 
-use egui::{Layout, Slider};
+use egui::{Color32, Layout, Pos2, Slider};
 use egui_extras::{Size, StripBuilder};
-use egui_plot::{Line, Plot, PlotPoint, PlotPoints, PlotResponse, Points};
+use egui_plot::{
+    Legend, Line, LineStyle, MarkerShape, Plot, PlotPoint, PlotPoints, PlotResponse, Points, VLine,
+};
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, IntoEnumIterator};
 
 use crate::{
     data::Attacks,
+    equipment_view::EquipmentView,
+    plot_export::{AxisTicks, Chart, ChartItem, ExportControls},
+    query::{weapon_points_for_attack, WeaponPoint},
+    selection::{SelectionBus, SelectionEvent},
     static_data::{POISE_DATA, WEAPON_CLASSES},
 };
 
+/// Extra distance in pixels around a point that still counts as hovering it
+pub const HOVER_TOLERANCE: f32 = 4.0;
+
+/// Every weapon of any of the attacks once, with its highest total, alphabetical within each class
+pub fn merge_weapon_points(
+    weapon_points_by_attack: &[BTreeMap<String, Vec<WeaponPoint>>],
+) -> BTreeMap<String, Vec<WeaponPoint>> {
+    let mut strongest_by_class = BTreeMap::<String, BTreeMap<String, WeaponPoint>>::new();
+
+    for weapon_point in weapon_points_by_attack
+        .iter()
+        .flat_map(|weapon_points_by_class| weapon_points_by_class.values())
+        .flatten()
+    {
+        let strongest = strongest_by_class
+            .entry(weapon_point.weapon_class.clone())
+            .or_default()
+            .entry(weapon_point.weapon.clone())
+            .or_insert_with(|| weapon_point.clone());
+
+        if weapon_point.total > strongest.total {
+            *strongest = weapon_point.clone();
+        }
+    }
+
+    strongest_by_class
+        .into_iter()
+        .map(|(weapon_class, strongest)| {
+            let weapon_points = strongest
+                .into_values()
+                .enumerate()
+                .map(|(i, weapon_point)| WeaponPoint {
+                    x: i as f64,
+                    ..weapon_point
+                })
+                .collect();
+
+            (weapon_class, weapon_points)
+        })
+        .collect()
+}
+
+/// Marker of the n-th selected attack and the symbol shown for it next to the attack's name
+pub const ATTACK_MARKERS: [(MarkerShape, &str); 8] = [
+    (MarkerShape::Circle, "●"),
+    (MarkerShape::Diamond, "◆"),
+    (MarkerShape::Square, "■"),
+    (MarkerShape::Up, "▲"),
+    (MarkerShape::Down, "▼"),
+    (MarkerShape::Cross, "✖"),
+    (MarkerShape::Plus, "✚"),
+    (MarkerShape::Asterisk, "✱"),
+];
+
+pub fn attack_marker(attack_index: usize) -> (MarkerShape, &'static str) {
+    ATTACK_MARKERS[attack_index % ATTACK_MARKERS.len()]
+}
+
+/// Named group of attacks that are selected together
+pub struct AttackPreset {
+    pub name: &'static str,
+    contains: fn(&Attacks) -> bool,
+}
+
+impl AttackPreset {
+    pub fn attacks(&self) -> Vec<Attacks> {
+        Attacks::all()
+            .into_iter()
+            .filter(|attack| (self.contains)(attack))
+            .collect()
+    }
+}
+
+pub const ATTACK_PRESETS: [AttackPreset; 7] = [
+    AttackPreset {
+        name: "One Handed R1 Chain",
+        contains: |attack| matches!(attack, Attacks::OneHandedR1Chain(_)),
+    },
+    AttackPreset {
+        name: "Two Handed R1 Chain",
+        contains: |attack| matches!(attack, Attacks::TwoHandedR1Chain(_)),
+    },
+    AttackPreset {
+        name: "One Handed R1 Running, Rolling, Backstep and Jumping",
+        contains: |attack| {
+            matches!(
+                attack,
+                Attacks::OneHandedR1Running
+                    | Attacks::OneHandedR1Rolling
+                    | Attacks::OneHandedR1Backstep
+                    | Attacks::OneHandedR1Jumping
+            )
+        },
+    },
+    AttackPreset {
+        name: "Two Handed R1 Running, Rolling, Backstep and Jumping",
+        contains: |attack| {
+            matches!(
+                attack,
+                Attacks::TwoHandedR1Running
+                    | Attacks::TwoHandedR1Rolling
+                    | Attacks::TwoHandedR1Backstep
+                    | Attacks::TwoHandedR1Jumping
+            )
+        },
+    },
+    AttackPreset {
+        name: "All One Handed R2 Variants",
+        contains: |attack| {
+            matches!(
+                attack,
+                Attacks::OneHandedR2Chain(_)
+                    | Attacks::OneHandedR2Charged(_)
+                    | Attacks::OneHandedR2Running
+                    | Attacks::OneHandedR2Jumping
+                    | Attacks::OneHandedR2Feint(_)
+            )
+        },
+    },
+    AttackPreset {
+        name: "All Two Handed R2 Variants",
+        contains: |attack| {
+            matches!(
+                attack,
+                Attacks::TwoHandedR2Chain(_)
+                    | Attacks::TwoHandedR2Charged(_)
+                    | Attacks::TwoHandedR2Running
+                    | Attacks::TwoHandedR2Jumping
+                    | Attacks::TwoHandedR2Feint(_)
+            )
+        },
+    },
+    AttackPreset {
+        name: "Criticals",
+        contains: |attack| matches!(attack, Attacks::Backstab(_) | Attacks::Riposte(_)),
+    },
+];
+
+/// Point closest to `pointer` within `max_distance`, all in screen coordinates
+pub fn nearest_weapon_point<'a>(
+    weapon_points: impl IntoIterator<Item = &'a WeaponPoint>,
+    pointer: Pos2,
+    to_screen: impl Fn(&WeaponPoint) -> Pos2,
+    max_distance: f32,
+) -> Option<&'a WeaponPoint> {
+    weapon_points
+        .into_iter()
+        .map(|weapon_point| (to_screen(weapon_point).distance(pointer), weapon_point))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, weapon_point)| weapon_point)
+}
+
+fn weapon_point_ui(
+    ui: &mut egui::Ui,
+    weapon_point: &WeaponPoint,
+    hyperarmor: f64,
+    armor_poise: u16,
+) {
+    ui.strong(&weapon_point.weapon);
+    ui.label(&weapon_point.weapon_class);
+    ui.label(weapon_point.attack.to_string());
+
+    ui.label(format!(
+        "Hits: {}",
+        weapon_point
+            .hits
+            .iter()
+            .map(|hit| hit.to_string())
+            .collect::<Vec<_>>()
+            .join(" + ")
+    ));
+    ui.label(format!("Total: {}", weapon_point.total));
+
+    if hyperarmor > 0.0 {
+        ui.label(format!(
+            "Hyperarmor Margin: {:+.1}",
+            weapon_point.total - hyperarmor
+        ));
+    }
+
+    ui.label(format!(
+        "Armor Poise Margin: {:+.1}",
+        weapon_point.total - armor_poise as f64
+    ));
+}
+
+/// Empty x positions between two weapon classes when grouped by class
+const CLASS_BAND_GAP: f64 = 3.0;
+
+/// Width of a weapon class' strip, classes are 1.0 apart
+const STRIP_WIDTH: f64 = 0.6;
+
+/// Weapon names are only shown on the x axis when at most this many positions are visible
+const MAX_LABELLED_WEAPONS: f64 = 20.0;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, EnumIter, Display, Serialize, Deserialize)]
+pub enum PlotLayout {
+    /// Alphabetical within each class, classes overlap
+    #[default]
+    #[strum(to_string = "Alphabetical")]
+    Alphabetical,
+    #[strum(to_string = "Sorted by Poise Damage")]
+    SortedByPoiseDamage,
+    #[strum(to_string = "Grouped by Class")]
+    GroupedByClass,
+    #[strum(to_string = "Strip per Class")]
+    Strip,
+}
+
+/// Consistent colour of a weapon class, independent of which classes are selected
+pub fn weapon_class_color(weapon_class: &str) -> Color32 {
+    let index = WEAPON_CLASSES
+        .keys()
+        .position(|class| class == weapon_class)
+        .unwrap_or_default();
+
+    // golden ratio steps spread neighbouring classes over the hue circle
+    egui::ecolor::Hsva::new((index as f32 * 0.618_034).fract(), 0.7, 0.9, 1.0).into()
+}
+
+/// Label of every x position that belongs to a single weapon or class
+pub type AxisLabels = BTreeMap<i64, String>;
+
+/// Positions the points of the given classes according to `layout`
+pub fn layout_weapon_points(
+    weapon_points_by_class: &[(&String, &Vec<WeaponPoint>)],
+    layout: PlotLayout,
+) -> (Vec<Vec<WeaponPoint>>, AxisLabels) {
+    let by_total = |a: &WeaponPoint, b: &WeaponPoint| {
+        a.total.total_cmp(&b.total).then(a.weapon.cmp(&b.weapon))
+    };
+
+    let mut laid_out = weapon_points_by_class
+        .iter()
+        .map(|(_, weapon_points)| (*weapon_points).clone())
+        .collect::<Vec<_>>();
+
+    let mut axis_labels = AxisLabels::new();
+
+    match layout {
+        PlotLayout::Alphabetical => {}
+        PlotLayout::SortedByPoiseDamage => {
+            let mut weapon_points = laid_out.iter_mut().flatten().collect::<Vec<_>>();
+
+            weapon_points.sort_by(|a, b| by_total(a, b));
+
+            for (i, weapon_point) in weapon_points.into_iter().enumerate() {
+                weapon_point.x = i as f64;
+                axis_labels.insert(i as i64, weapon_point.weapon.clone());
+            }
+        }
+        PlotLayout::GroupedByClass => {
+            let mut offset = 0.0;
+
+            for weapon_points in laid_out.iter_mut() {
+                weapon_points.sort_by(by_total);
+
+                for (i, weapon_point) in weapon_points.iter_mut().enumerate() {
+                    weapon_point.x = offset + i as f64;
+                    axis_labels.insert(weapon_point.x as i64, weapon_point.weapon.clone());
+                }
+
+                offset += weapon_points.len() as f64 + CLASS_BAND_GAP;
+            }
+        }
+        PlotLayout::Strip => {
+            for (class_index, weapon_points) in laid_out.iter_mut().enumerate() {
+                weapon_points.sort_by(by_total);
+
+                let count = weapon_points.len() as f64;
+
+                for (i, weapon_point) in weapon_points.iter_mut().enumerate() {
+                    weapon_point.x =
+                        class_index as f64 + STRIP_WIDTH * ((i as f64 + 0.5) / count - 0.5);
+                }
+
+                axis_labels.insert(
+                    class_index as i64,
+                    weapon_points_by_class[class_index].0.clone(),
+                );
+            }
+        }
+    }
+
+    (laid_out, axis_labels)
+}
+
+impl PlotLayout {
+    pub fn x_axis_label(&self) -> &'static str {
+        match self {
+            PlotLayout::Alphabetical => "Weapon (alphabetical per class)",
+            PlotLayout::SortedByPoiseDamage => "Weapon",
+            PlotLayout::GroupedByClass => "Weapon (grouped by class)",
+            PlotLayout::Strip => "Weapon Class",
+        }
+    }
+}
+
+/// What the plot draws for the selected attacks and weapon classes
+pub struct PlotSeries {
+    /// Attack index, weapon class and its points, every attack shares the x position of a weapon
+    pub series: Vec<(usize, String, Vec<WeaponPoint>)>,
+    pub axis_labels: AxisLabels,
+    pub class_boundaries: Vec<f64>,
+    pub min_x: f64,
+    pub max_x: f64,
+}
+
+pub fn plot_series(
+    weapon_points_by_attack: &[BTreeMap<String, Vec<WeaponPoint>>],
+    selected_weapon_classes: &BTreeMap<String, bool>,
+    layout: PlotLayout,
+) -> PlotSeries {
+    let is_class_selected = |weapon_class: &String| {
+        selected_weapon_classes
+            .get(weapon_class)
+            .copied()
+            .unwrap_or_default()
+    };
+
+    let merged_weapon_points = merge_weapon_points(weapon_points_by_attack);
+
+    let selected_weapon_points = merged_weapon_points
+        .iter()
+        .filter(|(weapon_class, _)| is_class_selected(weapon_class))
+        .collect::<Vec<_>>();
+
+    let (laid_out_weapon_points, axis_labels) =
+        layout_weapon_points(&selected_weapon_points, layout);
+
+    let x_by_weapon = laid_out_weapon_points
+        .iter()
+        .flatten()
+        .map(|weapon_point| (weapon_point.weapon.as_str(), weapon_point.x))
+        .collect::<HashMap<_, _>>();
+
+    let series = weapon_points_by_attack
+        .iter()
+        .enumerate()
+        .flat_map(|(attack_index, weapon_points_by_class)| {
+            weapon_points_by_class
+                .iter()
+                .filter(|(weapon_class, _)| is_class_selected(weapon_class))
+                .map(move |(weapon_class, weapon_points)| {
+                    (attack_index, weapon_class, weapon_points)
+                })
+        })
+        .map(|(attack_index, weapon_class, weapon_points)| {
+            let weapon_points = weapon_points
+                .iter()
+                .map(|weapon_point| WeaponPoint {
+                    x: x_by_weapon[weapon_point.weapon.as_str()],
+                    ..weapon_point.clone()
+                })
+                .collect::<Vec<_>>();
+
+            (attack_index, weapon_class.clone(), weapon_points)
+        })
+        .collect::<Vec<_>>();
+
+    let (min_x, max_x) = laid_out_weapon_points
+        .iter()
+        .flatten()
+        .map(|weapon_point| weapon_point.x)
+        .fold(None, |range: Option<(f64, f64)>, x| match range {
+            Some((min_x, max_x)) => Some((min_x.min(x), max_x.max(x))),
+            None => Some((x, x)),
+        })
+        .map(|(min_x, max_x)| (min_x - 0.5, max_x + 0.5))
+        .unwrap_or((0.0, 1.0));
+
+    // midway between the last point of a class and the first of the next
+    let class_boundaries = match layout {
+        PlotLayout::GroupedByClass | PlotLayout::Strip => laid_out_weapon_points
+            .iter()
+            .filter(|weapon_points| !weapon_points.is_empty())
+            .collect::<Vec<_>>()
+            .windows(2)
+            .map(|classes| {
+                let last = classes[0].iter().map(|p| p.x);
+                let first = classes[1].iter().map(|p| p.x);
+
+                (last.fold(f64::MIN, f64::max) + first.fold(f64::MAX, f64::min)) / 2.0
+            })
+            .collect::<Vec<_>>(),
+        _ => Vec::new(),
+    };
+
+    PlotSeries {
+        series,
+        axis_labels,
+        class_boundaries,
+        min_x,
+        max_x,
+    }
+}
+
+/// The plot as an exportable chart, the legend names attacks by name as the marker shows
+pub fn one_attack_chart(
+    plot_series: &PlotSeries,
+    selected_attacks: &[Attacks],
+    layout: PlotLayout,
+    point_radius: f32,
+    equipment_view: &EquipmentView,
+    overlay_lines: &[(String, f64)],
+) -> Chart {
+    let title = match selected_attacks.len() {
+        0..=3 => selected_attacks
+            .iter()
+            .map(|attack| attack.to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        count => format!("{} and {} more", selected_attacks[0], count - 1),
+    };
+
+    let mut chart = Chart::new(title, layout.x_axis_label(), "Poise Damage");
+
+    chart.x_ticks = match layout {
+        PlotLayout::Alphabetical => AxisTicks::Hidden,
+        _ => AxisTicks::Labels(plot_series.axis_labels.clone()),
+    };
+
+    for class_boundary in &plot_series.class_boundaries {
+        chart.items.push(ChartItem::VLine {
+            name: None,
+            color: Color32::GRAY,
+            is_dashed: true,
+            x: *class_boundary,
+        });
+    }
+
+    for (attack_index, weapon_class, weapon_points) in &plot_series.series {
+        chart.items.push(ChartItem::Points {
+            name: Some(match selected_attacks.len() {
+                1 => weapon_class.clone(),
+                _ => selected_attacks[*attack_index].to_string(),
+            }),
+            color: weapon_class_color(weapon_class),
+            shape: attack_marker(*attack_index).0,
+            radius: point_radius,
+            points: weapon_points
+                .iter()
+                .map(|weapon_point| [weapon_point.x, weapon_point.total])
+                .collect(),
+        });
+    }
+
+    let horizontal_line = |name: &str, color, is_dashed, y| ChartItem::Line {
+        name: Some(name.to_string()),
+        color,
+        is_dashed,
+        points: vec![[plot_series.min_x, y], [plot_series.max_x, y]],
+    };
+
+    chart.items.push(horizontal_line(
+        "Armor Poise",
+        Color32::from_rgb(255, 0, 0),
+        false,
+        equipment_view.armor_poise as f64,
+    ));
+    chart.items.push(horizontal_line(
+        "Hyperarmor",
+        Color32::from_rgb(0, 255, 0),
+        false,
+        equipment_view.hyperarmor.unwrap_or_default(),
+    ));
+
+    for (name, poise) in overlay_lines {
+        chart
+            .items
+            .push(horizontal_line(name, Color32::LIGHT_GRAY, true, *poise));
+    }
+
+    chart
+}
+
 #[derive(Default)]
 struct PlotConfig {
     point_radius: f32,
+    layout: PlotLayout,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OneAttackPlotViewState {
+    pub is_open: bool,
+    pub selected_attacks: Vec<Attacks>,
+    pub selected_weapon_classes: BTreeMap<String, bool>,
+    pub point_radius: f32,
+    #[serde(default)]
+    pub layout: PlotLayout,
 }
 
 #[derive(Default)]
 pub struct OneAttackPlotView {
     pub is_open: bool,
-    weapon_class_ids: HashMap<egui::Id, String>,
-    selected_attack: Option<Attacks>,
+    selected_attacks: Vec<Attacks>,
+    attack_to_add: Option<Attacks>,
     is_attack_changed: bool,
-    poise_damage_values_for_attack_by_class: BTreeMap<String, Vec<(String, f64)>>,
+    /// Same order as `selected_attacks`
+    weapon_points_by_attack: Vec<BTreeMap<String, Vec<WeaponPoint>>>,
     selected_weapon_classes: BTreeMap<String, bool>,
-    hovered_weapon: Option<String>,
-    hovered_weapon_class: Option<String>,
+    hovered_weapon_point: Option<WeaponPoint>,
+    pinned: Option<(String, Attacks)>,
     plot_config: PlotConfig,
+    export_controls: ExportControls,
     rect: Option<egui::Rect>,
 }
 
 impl OneAttackPlotView {
     pub fn new() -> Self {
-        let selected_attack = Attacks::OneHandedR1Chain(0);
+        let selected_attacks = vec![Attacks::OneHandedR1Chain(0)];
 
-        let poise_damage_values_for_attack_by_class =
-            POISE_DATA.get_poise_damage_values_for_attack_by_class(&selected_attack, &Some(1.0));
-
-        let weapon_class_ids = WEAPON_CLASSES
-            .keys()
-            .cloned()
-            .map(|class| (egui::Id::new(class.clone()), class))
-            .collect::<HashMap<egui::Id, String>>();
+        let weapon_points_by_attack = selected_attacks
+            .iter()
+            .map(|attack| weapon_points_for_attack(&POISE_DATA, attack, &Some(1.0)))
+            .collect();
 
         Self {
             is_open: false,
-            weapon_class_ids,
-            selected_attack: Some(selected_attack),
+            selected_attacks,
             is_attack_changed: false,
-            poise_damage_values_for_attack_by_class,
+            weapon_points_by_attack,
             selected_weapon_classes: WEAPON_CLASSES
                 .keys()
                 .cloned()
                 .map(|class| (class, true))
                 .collect(),
-            hovered_weapon: None,
-            hovered_weapon_class: None,
+            hovered_weapon_point: None,
+            pinned: None,
             plot_config: PlotConfig {
                 point_radius: 3.0,
                 ..Default::default()
@@ -62,14 +556,72 @@ impl OneAttackPlotView {
         }
     }
 
+    pub fn state(&self) -> OneAttackPlotViewState {
+        OneAttackPlotViewState {
+            is_open: self.is_open,
+            selected_attacks: self.selected_attacks.clone(),
+            selected_weapon_classes: self.selected_weapon_classes.clone(),
+            point_radius: self.plot_config.point_radius,
+            layout: self.plot_config.layout,
+        }
+    }
+
+    /// Weapon classes that are not in the data anymore are dropped, new ones stay selected
+    pub fn restore(
+        &mut self,
+        state: OneAttackPlotViewState,
+        incoming_poise_damage_multiplier: &Option<f64>,
+    ) {
+        self.is_open = state.is_open;
+        self.plot_config.point_radius = state.point_radius;
+        self.plot_config.layout = state.layout;
+
+        for (weapon_class, is_selected) in state.selected_weapon_classes {
+            if let Some(selected) = self.selected_weapon_classes.get_mut(&weapon_class) {
+                *selected = is_selected;
+            }
+        }
+
+        self.selected_attacks = state.selected_attacks;
+        self.weapon_points_by_attack = self
+            .selected_attacks
+            .iter()
+            .map(|attack| {
+                weapon_points_for_attack(&POISE_DATA, attack, incoming_poise_damage_multiplier)
+            })
+            .collect();
+    }
+
+    /// Weapon classes shown in the plot, other plots filter by them too
+    pub fn selected_weapon_classes(&self) -> &BTreeMap<String, bool> {
+        &self.selected_weapon_classes
+    }
+
+    /// An attack selected elsewhere replaces the plotted attacks, unless it is already one of them
+    pub fn on_selection_event(&mut self, event: &SelectionEvent) {
+        if let SelectionEvent::AttackSelected(attack) = event {
+            if !self.selected_attacks.contains(attack) {
+                self.selected_attacks = vec![attack.clone()];
+                self.is_attack_changed = true;
+            }
+        }
+    }
+
     pub fn show(
         &mut self,
         ui: &mut egui::Ui,
-        is_changed_incoming_poise_damage_multiplier: &bool,
-        incoming_poise_damage_multiplier: &Option<f64>,
-        hyperarmor: &Option<f64>,
-        armor_poise: &u16,
+        equipment_view: &EquipmentView,
+        overlay_lines: &[(String, f64)],
+        selection_bus: &mut SelectionBus,
     ) {
+        let EquipmentView {
+            is_changed_incoming_poise_damage_multiplier,
+            incoming_poise_damage_multiplier,
+            hyperarmor,
+            armor_poise,
+            ..
+        } = equipment_view;
+
         const INITIAL_WINDOW_SIZE: [f32; 2] = [600.0, 400.0];
 
         let window = egui::Window::new("One Attack Plot")
@@ -84,186 +636,520 @@ impl OneAttackPlotView {
                 }
 
                 if self.is_attack_changed || *is_changed_incoming_poise_damage_multiplier {
-                    self.poise_damage_values_for_attack_by_class = POISE_DATA
-                        .get_poise_damage_values_for_attack_by_class(
-                            self.selected_attack.as_ref().unwrap(),
-                            incoming_poise_damage_multiplier,
-                        );
+                    self.weapon_points_by_attack = self
+                        .selected_attacks
+                        .iter()
+                        .map(|attack| {
+                            weapon_points_for_attack(
+                                &POISE_DATA,
+                                attack,
+                                incoming_poise_damage_multiplier,
+                            )
+                        })
+                        .collect();
+
+                    self.is_attack_changed = false;
                 }
 
-                if let Some(_selected_attack) = &self.selected_attack {
-                    StripBuilder::new(ui)
-                        .cell_layout(Layout::top_down(egui::Align::Min))
-                        .size(Size::relative(0.7))
-                        .size(Size::initial(200.0))
-                        .horizontal(|mut strip| {
-                            strip.cell(|ui| {
-                                let plot = Plot::new("One Attack Plot");
-
-                                let PlotResponse {
-                                    response: _,
-                                    inner:
-                                        (
-                                            _screen_pos,
-                                            pointer_coordinate,
-                                            _pointer_coordinate_drag_delta,
-                                            _bounds,
-                                            _hovered,
+                let layout = self.plot_config.layout;
+
+                let plot_series = plot_series(
+                    &self.weapon_points_by_attack,
+                    &self.selected_weapon_classes,
+                    layout,
+                );
+
+                let PlotSeries {
+                    series,
+                    axis_labels,
+                    class_boundaries,
+                    min_x,
+                    max_x,
+                } = &plot_series;
+                let (min_x, max_x) = (*min_x, *max_x);
+
+                StripBuilder::new(ui)
+                    .cell_layout(Layout::top_down(egui::Align::Min))
+                    .size(Size::relative(0.7))
+                    .size(Size::initial(200.0))
+                    .horizontal(|mut strip| {
+                        strip.cell(|ui| {
+                            let plot = Plot::new("One Attack Plot")
+                                .legend(Legend::default())
+                                .show_x(false)
+                                .show_y(false)
+                                .x_axis_label(layout.x_axis_label())
+                                .y_axis_label("Poise Damage")
+                                .x_axis_formatter(|mark, range| {
+                                    let is_zoomed_in = layout == PlotLayout::Strip
+                                        || range.end() - range.start() <= MAX_LABELLED_WEAPONS;
+
+                                    let x = mark.value.round();
+
+                                    match is_zoomed_in && (mark.value - x).abs() < 1e-6 {
+                                        true => axis_labels
+                                            .get(&(x as i64))
+                                            .cloned()
+                                            .unwrap_or_default(),
+                                        false => String::new(),
+                                    }
+                                });
+
+                            let pinned_weapon_point = series
+                                .iter()
+                                .flat_map(|(_, _, weapon_points)| weapon_points)
+                                .find(|weapon_point| {
+                                    self.pinned.as_ref()
+                                        == Some(&(
+                                            weapon_point.weapon.clone(),
+                                            weapon_point.attack.clone(),
+                                        ))
+                                });
+
+                            let pinned_color = ui.visuals().strong_text_color();
+
+                            let PlotResponse {
+                                response,
+                                inner: hovered_weapon_point,
+                                ..
+                            } = plot.show(ui, |plot_ui| {
+                                for (attack_index, weapon_class, weapon_points) in series {
+                                    let (shape, symbol) = attack_marker(*attack_index);
+
+                                    let name = match self.selected_attacks.len() {
+                                        1 => weapon_class.to_string(),
+                                        _ => format!(
+                                            "{} {}",
+                                            symbol, self.selected_attacks[*attack_index]
                                         ),
-                                    hovered_plot_item,
-                                    ..
-                                } = plot.show(ui, |plot_ui| {
-                                    let mut max_x_length = 0usize;
+                                    };
 
-                                    for (weapon_class, poise_damage_values) in
-                                        self.poise_damage_values_for_attack_by_class.iter()
-                                    {
-                                        if !self.selected_weapon_classes.get(weapon_class).unwrap()
-                                        {
-                                            continue;
-                                        }
+                                    let points = weapon_points
+                                        .iter()
+                                        .map(|weapon_point| [weapon_point.x, weapon_point.total])
+                                        .collect::<PlotPoints>();
+
+                                    let points = Points::new(points)
+                                        .name(name)
+                                        .shape(shape)
+                                        .filled(true)
+                                        .color(weapon_class_color(weapon_class))
+                                        .radius(self.plot_config.point_radius);
 
-                                        let points = poise_damage_values.iter().enumerate().map(
-                                            |(i, (_weapon, poise_damage))| {
-                                                [i as f64, *poise_damage]
-                                            },
-                                        );
+                                    plot_ui.points(points);
+                                }
 
-                                        max_x_length = max_x_length.max(points.len());
+                                for class_boundary in class_boundaries {
+                                    plot_ui.vline(
+                                        VLine::new(*class_boundary)
+                                            .color(Color32::GRAY)
+                                            .style(LineStyle::dotted_dense()),
+                                    );
+                                }
 
-                                        let points = points.collect::<PlotPoints>();
+                                if let Some(pinned_weapon_point) = pinned_weapon_point {
+                                    plot_ui.points(
+                                        Points::new(vec![[
+                                            pinned_weapon_point.x,
+                                            pinned_weapon_point.total,
+                                        ]])
+                                        .shape(MarkerShape::Diamond)
+                                        .filled(false)
+                                        .radius(self.plot_config.point_radius * 2.5)
+                                        .color(pinned_color),
+                                    );
+                                }
 
-                                        let points = Points::new(points)
-                                            .id(weapon_class.clone().into())
-                                            .radius(self.plot_config.point_radius);
+                                plot_ui.line(
+                                    Line::new(vec![
+                                        [min_x, *armor_poise as f64],
+                                        [max_x, *armor_poise as f64],
+                                    ])
+                                    .color(egui::Color32::from_rgb(255, 0, 0))
+                                    .name("Armor Poise"),
+                                );
 
-                                        plot_ui.points(points);
-                                    }
+                                plot_ui.line(
+                                    Line::new(vec![
+                                        [min_x, hyperarmor.unwrap_or(0.0)],
+                                        [max_x, hyperarmor.unwrap_or(0.0)],
+                                    ])
+                                    .color(egui::Color32::from_rgb(0, 255, 0))
+                                    .name("Hyperarmor"),
+                                );
 
+                                for (name, poise) in overlay_lines {
                                     plot_ui.line(
-                                        Line::new(vec![
-                                            [0.0, *armor_poise as f64],
-                                            [max_x_length as f64, *armor_poise as f64],
-                                        ])
-                                        .color(egui::Color32::from_rgb(255, 0, 0)),
+                                        Line::new(vec![[min_x, *poise], [max_x, *poise]])
+                                            .style(LineStyle::dashed_loose())
+                                            .name(name),
                                     );
+                                }
 
-                                    plot_ui.line(
-                                        Line::new(vec![
-                                            [0.0, hyperarmor.unwrap_or(0.0)],
-                                            [max_x_length as f64, hyperarmor.unwrap_or(0.0)],
-                                        ])
-                                        .color(egui::Color32::from_rgb(0, 255, 0)),
+                                let pointer = plot_ui
+                                    .pointer_coordinate()
+                                    .map(|coordinate| plot_ui.screen_from_plot(coordinate))?;
+
+                                nearest_weapon_point(
+                                    series
+                                        .iter()
+                                        .flat_map(|(_, _, weapon_points)| weapon_points),
+                                    pointer,
+                                    |weapon_point| {
+                                        plot_ui.screen_from_plot(PlotPoint::new(
+                                            weapon_point.x,
+                                            weapon_point.total,
+                                        ))
+                                    },
+                                    self.plot_config.point_radius + HOVER_TOLERANCE,
+                                )
+                                .cloned()
+                            });
+
+                            if let Some(weapon_point) = &hovered_weapon_point {
+                                response.clone().on_hover_ui_at_pointer(|ui| {
+                                    weapon_point_ui(
+                                        ui,
+                                        weapon_point,
+                                        hyperarmor.unwrap_or_default(),
+                                        *armor_poise,
                                     );
+                                });
+                            }
 
-                                    (
-                                        plot_ui.screen_from_plot(PlotPoint::new(0.0, 0.0)),
-                                        plot_ui.pointer_coordinate(),
-                                        plot_ui.pointer_coordinate_drag_delta(),
-                                        plot_ui.plot_bounds(),
-                                        plot_ui.response().hovered(),
-                                    )
+                            if response.clicked() {
+                                self.pinned = hovered_weapon_point.as_ref().map(|weapon_point| {
+                                    (weapon_point.weapon.clone(), weapon_point.attack.clone())
                                 });
 
-                                if let Some(hovered_plot_item) = hovered_plot_item {
-                                    self.hovered_weapon_class = Some(
-                                        self.weapon_class_ids
-                                            .get(&hovered_plot_item)
-                                            .unwrap()
-                                            .clone(),
-                                    );
+                                if let Some((weapon, _)) = &self.pinned {
+                                    selection_bus
+                                        .publish(SelectionEvent::WeaponSelected(weapon.clone()));
+                                }
+                            }
 
-                                    let weapon_index = pointer_coordinate.unwrap().x as usize;
+                            self.hovered_weapon_point = hovered_weapon_point;
+                        });
+                        strip.cell(|ui| {
+                            ui.vertical(|ui| {
+                                ui.horizontal(|ui| {
+                                    Attacks::combobox(ui, &mut self.attack_to_add);
 
-                                    let weapon = WEAPON_CLASSES
-                                        .get(self.hovered_weapon_class.as_ref().unwrap())
-                                        .and_then(|weapons| weapons.get(weapon_index))
-                                        .unwrap_or(&"".to_string())
-                                        .clone();
+                                    let attack_to_add = self
+                                        .attack_to_add
+                                        .clone()
+                                        .filter(|attack| !self.selected_attacks.contains(attack));
 
-                                    self.hovered_weapon = Some(weapon);
+                                    if ui
+                                        .add_enabled(
+                                            attack_to_add.is_some(),
+                                            egui::Button::new("Add"),
+                                        )
+                                        .clicked()
+                                    {
+                                        let attack = attack_to_add.unwrap();
+
+                                        selection_bus.publish(SelectionEvent::AttackSelected(
+                                            attack.clone(),
+                                        ));
+
+                                        self.selected_attacks.push(attack);
+                                        self.is_attack_changed = true;
+                                    }
+                                });
+
+                                ui.menu_button("Presets", |ui| {
+                                    for preset in &ATTACK_PRESETS {
+                                        if ui.button(preset.name).clicked() {
+                                            self.selected_attacks = preset.attacks();
+                                            self.is_attack_changed = true;
+                                            ui.close_menu();
+                                        }
+                                    }
+                                });
+
+                                let mut removed_attack = None;
+
+                                for (attack_index, attack) in
+                                    self.selected_attacks.iter().enumerate()
+                                {
+                                    ui.horizontal(|ui| {
+                                        ui.button("✖").clicked().then(|| {
+                                            removed_attack = Some(attack_index);
+                                        });
+
+                                        ui.label(format!(
+                                            "{} {}",
+                                            attack_marker(attack_index).1,
+                                            attack
+                                        ));
+                                    });
                                 }
-                            });
-                            strip.cell(|ui| {
-                                ui.vertical(|ui| {
-                                    self.is_attack_changed =
-                                        Attacks::combobox(ui, &mut self.selected_attack);
-
-                                    ui.add(
-                                        Slider::from_get_set(0.0..=20.0, |value| {
-                                            if let Some(value) = value {
-                                                self.plot_config.point_radius = value as f32;
-                                            }
-                                            self.plot_config.point_radius as f64
-                                        })
-                                        .text("Point Radius"),
+
+                                if let Some(attack_index) = removed_attack {
+                                    self.selected_attacks.remove(attack_index);
+                                    self.is_attack_changed = true;
+                                }
+
+                                ui.separator();
+
+                                ui.add(
+                                    Slider::from_get_set(0.0..=20.0, |value| {
+                                        if let Some(value) = value {
+                                            self.plot_config.point_radius = value as f32;
+                                        }
+                                        self.plot_config.point_radius as f64
+                                    })
+                                    .text("Point Radius"),
+                                );
+
+                                egui::ComboBox::from_label("Layout")
+                                    .selected_text(self.plot_config.layout.to_string())
+                                    .show_ui(ui, |ui| {
+                                        for layout in PlotLayout::iter() {
+                                            ui.selectable_value(
+                                                &mut self.plot_config.layout,
+                                                layout,
+                                                layout.to_string(),
+                                            );
+                                        }
+                                    });
+
+                                if let Some(format) = self.export_controls.show(ui) {
+                                    let chart = one_attack_chart(
+                                        &plot_series,
+                                        &self.selected_attacks,
+                                        layout,
+                                        self.plot_config.point_radius,
+                                        equipment_view,
+                                        overlay_lines,
                                     );
 
-                                    ui.separator();
+                                    self.export_controls
+                                        .export(format, &[("one_attack_plot", chart)]);
+                                }
 
-                                    if let Some(hovered_weapon) = &self.hovered_weapon {
-                                        ui.label(format!("Hovered Weapon: {}", hovered_weapon));
-                                    }
+                                ui.separator();
+
+                                if let Some(weapon_point) = &self.hovered_weapon_point {
+                                    ui.label(format!("Hovered Weapon: {}", weapon_point.weapon));
+                                }
 
+                                let pinned_weapon_point = self
+                                    .weapon_points_by_attack
+                                    .iter()
+                                    .flat_map(|weapon_points_by_class| {
+                                        weapon_points_by_class.values()
+                                    })
+                                    .flatten()
+                                    .find(|weapon_point| {
+                                        self.pinned.as_ref()
+                                            == Some(&(
+                                                weapon_point.weapon.clone(),
+                                                weapon_point.attack.clone(),
+                                            ))
+                                    });
+
+                                if let Some(weapon_point) = pinned_weapon_point {
                                     ui.separator();
 
-                                    let mut unselect_all_other = None;
+                                    weapon_point_ui(
+                                        ui,
+                                        weapon_point,
+                                        hyperarmor.unwrap_or_default(),
+                                        *armor_poise,
+                                    );
 
-                                    ui.horizontal_wrapped(|ui| {
-                                        for (weapon_class, is_selected) in
-                                            self.selected_weapon_classes.iter_mut()
-                                        {
-                                            ui.selectable_label(*is_selected, weapon_class)
-                                                .clicked()
-                                                .then(|| {
-                                                    *is_selected = !*is_selected;
-
-                                                    if ui.ctx().input(|i| i.modifiers.ctrl) {
-                                                        unselect_all_other =
-                                                            Some(weapon_class.clone());
-                                                    }
-                                                });
-                                        }
+                                    ui.button("Unpin").clicked().then(|| {
+                                        self.pinned = None;
                                     });
+                                }
 
-                                    if let Some(unselect_all_other) = unselect_all_other {
-                                        for (weapon_class, is_selected) in
-                                            self.selected_weapon_classes.iter_mut()
-                                        {
-                                            if *weapon_class != unselect_all_other {
-                                                *is_selected = false;
-                                            }
+                                ui.separator();
+
+                                let mut unselect_all_other = None;
+
+                                ui.horizontal_wrapped(|ui| {
+                                    for (weapon_class, is_selected) in
+                                        self.selected_weapon_classes.iter_mut()
+                                    {
+                                        ui.selectable_label(*is_selected, weapon_class)
+                                            .clicked()
+                                            .then(|| {
+                                                *is_selected = !*is_selected;
+
+                                                if ui.ctx().input(|i| i.modifiers.ctrl) {
+                                                    unselect_all_other = Some(weapon_class.clone());
+                                                }
+                                            });
+                                    }
+                                });
+
+                                if let Some(unselect_all_other) = unselect_all_other {
+                                    for (weapon_class, is_selected) in
+                                        self.selected_weapon_classes.iter_mut()
+                                    {
+                                        if *weapon_class != unselect_all_other {
+                                            *is_selected = false;
+                                        }
 
-                                            if *weapon_class == unselect_all_other {
-                                                *is_selected = true;
-                                            }
+                                        if *weapon_class == unselect_all_other {
+                                            *is_selected = true;
                                         }
                                     }
+                                }
 
-                                    ui.horizontal(|ui| {
-                                        ui.button("Deselect All").clicked().then(|| {
-                                            for is_selected in
-                                                self.selected_weapon_classes.values_mut()
-                                            {
-                                                *is_selected = false;
-                                            }
-                                        });
+                                ui.horizontal(|ui| {
+                                    ui.button("Deselect All").clicked().then(|| {
+                                        for is_selected in self.selected_weapon_classes.values_mut()
+                                        {
+                                            *is_selected = false;
+                                        }
+                                    });
 
-                                        ui.button("Select All").clicked().then(|| {
-                                            for is_selected in
-                                                self.selected_weapon_classes.values_mut()
-                                            {
-                                                *is_selected = true;
-                                            }
-                                        });
+                                    ui.button("Select All").clicked().then(|| {
+                                        for is_selected in self.selected_weapon_classes.values_mut()
+                                        {
+                                            *is_selected = true;
+                                        }
                                     });
                                 });
-                            })
-                        });
-                }
+                            });
+                        })
+                    });
             })
             .unwrap();
 
         self.rect = Some(window.response.interact_rect);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::WeaponPoiseDamage;
+
+    #[test]
+    fn test_merge_weapon_points() {
+        let claymore = WeaponPoiseDamage::test_weapon("Claymore", "Greatsword")
+            .with_hits(|w| &mut w.one_handed.r1.chain.one, vec![60])
+            .with_hits(|w| &mut w.one_handed.r1.chain.two, vec![65]);
+
+        let zweihander = WeaponPoiseDamage::test_weapon("Zweihander", "Colossal Sword")
+            .with_hits(|w| &mut w.one_handed.r1.chain.two, vec![90]);
+
+        let poise_data = BTreeMap::from([
+            ("Claymore".to_string(), claymore),
+            ("Zweihander".to_string(), zweihander),
+        ]);
+
+        let weapon_points_by_attack = [Attacks::OneHandedR1Chain(0), Attacks::OneHandedR1Chain(1)]
+            .iter()
+            .map(|attack| weapon_points_for_attack(&poise_data, attack, &None))
+            .collect::<Vec<_>>();
+
+        let merged = merge_weapon_points(&weapon_points_by_attack);
+
+        assert_eq!(merged["Greatsword"].len(), 1);
+        assert_eq!(merged["Greatsword"][0].total, 65.0);
+        assert_eq!(merged["Greatsword"][0].attack, Attacks::OneHandedR1Chain(1));
+        assert_eq!(merged["Colossal Sword"][0].weapon, "Zweihander");
+    }
+
+    #[test]
+    fn test_attack_presets() {
+        let two_handed_r2 = ATTACK_PRESETS
+            .iter()
+            .find(|preset| preset.name == "All Two Handed R2 Variants")
+            .unwrap()
+            .attacks();
+
+        assert!(two_handed_r2.contains(&Attacks::TwoHandedR2Running));
+        assert!(two_handed_r2.contains(&Attacks::TwoHandedR2Chain(1)));
+        assert!(!two_handed_r2.contains(&Attacks::OneHandedR2Running));
+
+        assert!(ATTACK_PRESETS
+            .iter()
+            .all(|preset| !preset.attacks().is_empty()));
+    }
+
+    #[test]
+    fn test_layout_weapon_points() {
+        let weapon_point = |weapon: &str, x: f64, total: f64| WeaponPoint {
+            weapon: weapon.to_string(),
+            weapon_class: String::new(),
+            attack: Attacks::OneHandedR1Chain(0),
+            x,
+            hits: vec![total],
+            total,
+        };
+
+        let daggers = vec![
+            weapon_point("Dagger", 0.0, 20.0),
+            weapon_point("Misericorde", 1.0, 10.0),
+        ];
+        let greatswords = vec![weapon_point("Claymore", 0.0, 60.0)];
+
+        let dagger_class = "Dagger".to_string();
+        let greatsword_class = "Greatsword".to_string();
+
+        let weapon_points_by_class = [(&dagger_class, &daggers), (&greatsword_class, &greatswords)];
+
+        let x_of = |layout| {
+            let (laid_out, axis_labels) = layout_weapon_points(&weapon_points_by_class, layout);
+
+            let x = laid_out
+                .iter()
+                .flatten()
+                .map(|weapon_point| (weapon_point.weapon.clone(), weapon_point.x))
+                .collect::<BTreeMap<_, _>>();
+
+            (x, axis_labels)
+        };
+
+        let (x, _) = x_of(PlotLayout::Alphabetical);
+        assert_eq!((x["Dagger"], x["Claymore"]), (0.0, 0.0));
+
+        let (x, axis_labels) = x_of(PlotLayout::SortedByPoiseDamage);
+        assert_eq!(
+            (x["Misericorde"], x["Dagger"], x["Claymore"]),
+            (0.0, 1.0, 2.0)
+        );
+        assert_eq!(axis_labels[&2], "Claymore");
+
+        let (x, _) = x_of(PlotLayout::GroupedByClass);
+        assert_eq!(x["Claymore"], 2.0 + CLASS_BAND_GAP);
+
+        let (x, axis_labels) = x_of(PlotLayout::Strip);
+        assert!(x["Misericorde"] < 0.0 && x["Dagger"] > 0.0);
+        assert_eq!(x["Claymore"], 1.0);
+        assert_eq!(axis_labels[&1], "Greatsword");
+    }
+
+    #[test]
+    fn test_nearest_weapon_point() {
+        let weapon_point = |weapon: &str, x: f64, total: f64| WeaponPoint {
+            weapon: weapon.to_string(),
+            weapon_class: "Greatsword".to_string(),
+            attack: Attacks::OneHandedR1Chain(0),
+            x,
+            hits: vec![total],
+            total,
+        };
+
+        let weapon_points = [
+            weapon_point("Claymore", 0.0, 0.0),
+            weapon_point("Zweihander", 10.0, 0.0),
+        ];
+
+        let to_screen = |weapon_point: &WeaponPoint| {
+            Pos2::new(weapon_point.x as f32, weapon_point.total as f32)
+        };
+
+        let nearest = |pointer| nearest_weapon_point(&weapon_points, pointer, to_screen, 3.0);
+
+        assert_eq!(nearest(Pos2::new(8.0, 1.0)).unwrap().weapon, "Zweihander");
+        assert_eq!(nearest(Pos2::new(1.0, 1.0)).unwrap().weapon, "Claymore");
+        assert_eq!(nearest(Pos2::new(5.0, 0.0)), None);
+    }
+}
@@ -0,0 +1,303 @@
+use egui::{Layout, Slider};
+use egui_extras::{Column, TableBuilder};
+
+use crate::{
+    data::{Attacks, WeaponPoiseDamage},
+    equipment_view::{enemy_poise_and_multiplier, hits_to_stagger, EquipmentView},
+    static_data::POISE_DATA,
+    table_export::{Table, TableExportControls},
+    weapon_select_view::WeaponSelectView,
+};
+
+type Combo = (&'static str, fn(u8) -> Attacks);
+
+const COMBOS: [Combo; 6] = [
+    ("One Handed R1 Chain", Attacks::OneHandedR1Chain),
+    ("One Handed R2 Chain", Attacks::OneHandedR2Chain),
+    ("Two Handed R1 Chain", Attacks::TwoHandedR1Chain),
+    ("Two Handed R2 Chain", Attacks::TwoHandedR2Chain),
+    ("Paired L1 Chain", Attacks::PairedL1Chain),
+    ("Off Hand R1 Chain", Attacks::OffHandR1Chain),
+];
+
+/// Steps of a chain needed to break `poise`, restarting the chain once it ends
+pub fn steps_to_stagger(chain_poise_damage: &[f64], poise: f64) -> Option<usize> {
+    let chain_total = chain_poise_damage.iter().sum::<f64>();
+
+    if chain_total <= 0.0 {
+        return None;
+    }
+
+    let full_chains = ((poise / chain_total).ceil() as usize).saturating_sub(1);
+    let mut accumulated = full_chains as f64 * chain_total;
+
+    for (i, poise_damage) in chain_poise_damage.iter().cycle().enumerate() {
+        accumulated += poise_damage;
+
+        if accumulated >= poise {
+            return Some(full_chains * chain_poise_damage.len() + i + 1);
+        }
+    }
+
+    unreachable!()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutgoingAttack {
+    pub attack: Attacks,
+    pub poise_damage: f64,
+    pub hits_to_stagger: Option<u32>,
+}
+
+/// Every attack of `weapon` ranked by the hits needed to break `poise`
+pub fn rank_attacks(
+    weapon: &WeaponPoiseDamage,
+    multiplier: f64,
+    poise: f64,
+) -> Vec<OutgoingAttack> {
+    let mut ranking = weapon
+        .attacks()
+        .map(|(attack, poise_damage_values)| {
+            let poise_damage = poise_damage_values.total() as f64 * multiplier;
+
+            OutgoingAttack {
+                attack,
+                poise_damage,
+                hits_to_stagger: hits_to_stagger(poise_damage, poise),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    ranking.sort_by(|a, b| {
+        a.hits_to_stagger
+            .unwrap_or(u32::MAX)
+            .cmp(&b.hits_to_stagger.unwrap_or(u32::MAX))
+            .then(b.poise_damage.total_cmp(&a.poise_damage))
+    });
+
+    ranking
+}
+
+pub fn ranking_table(weapon: &WeaponPoiseDamage, ranking: &[OutgoingAttack]) -> Table {
+    let mut table = Table::new(
+        format!("Outgoing Poise of {}", weapon.name),
+        &["Attack", "Poise Damage", "Hits to Stagger"],
+    );
+
+    for outgoing_attack in ranking {
+        table.push_row(vec![
+            outgoing_attack.attack.to_string(),
+            format!("{:.1}", outgoing_attack.poise_damage),
+            outgoing_attack
+                .hits_to_stagger
+                .map(|hits| hits.to_string())
+                .unwrap_or("-".to_string()),
+        ]);
+    }
+
+    table
+}
+
+pub fn combo_poise_damage(
+    weapon: &WeaponPoiseDamage,
+    combo: fn(u8) -> Attacks,
+    multiplier: f64,
+) -> Vec<f64> {
+    (0..6)
+        .map_while(|step| weapon.get_poise_damage_by_attack(&combo(step)))
+        .filter(|poise_damage_values| !poise_damage_values.is_empty())
+        .map(|poise_damage_values| poise_damage_values.total() as f64 * multiplier)
+        .collect()
+}
+
+#[derive(Default)]
+pub struct OutgoingPoiseView {
+    pub is_open: bool,
+    enemy_weapon: Option<String>,
+    enemy_attack: Option<Attacks>,
+    enemy_armor_poise: u16,
+    is_enemy_in_recovery: bool,
+    weapon_select_view: WeaponSelectView,
+    table_export_controls: TableExportControls,
+}
+
+impl OutgoingPoiseView {
+    pub fn new() -> Self {
+        Self {
+            is_open: false,
+            enemy_weapon: None,
+            enemy_attack: None,
+            enemy_armor_poise: 0,
+            is_enemy_in_recovery: false,
+            weapon_select_view: WeaponSelectView::new(),
+            table_export_controls: TableExportControls::default(),
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, equipment_view: &EquipmentView) {
+        let EquipmentView {
+            selected_weapon,
+            selected_modifiers,
+            ..
+        } = equipment_view;
+
+        egui::Window::new("Outgoing Poise")
+            .id("Outgoing Poise Window".into())
+            .resizable(true)
+            .title_bar(true)
+            .open(&mut self.is_open)
+            .show(ui.ctx(), |ui| {
+                ui.heading("Enemy");
+
+                ui.horizontal(|ui| {
+                    ui.label("Enemy Weapon: ");
+
+                    let text = match &self.enemy_weapon {
+                        Some(weapon) => weapon,
+                        None => "None",
+                    };
+                    ui.button(text).clicked().then(|| {
+                        self.weapon_select_view.is_open = true;
+                    });
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Enemy Attack: ");
+
+                    Attacks::combobox(ui, &mut self.enemy_attack);
+                });
+
+                ui.add(Slider::new(&mut self.enemy_armor_poise, 0..=150).text("Enemy Armor Poise"));
+
+                ui.checkbox(&mut self.is_enemy_in_recovery, "Enemy In Recovery");
+
+                if self.weapon_select_view.is_open {
+                    self.weapon_select_view.show(ui, "Enemy");
+                }
+
+                if let Some(selected_weapon) = self.weapon_select_view.selected_weapon.take() {
+                    self.enemy_weapon = Some(selected_weapon);
+                    self.weapon_select_view.is_open = false;
+                }
+
+                ui.separator();
+
+                let Some(weapon) = selected_weapon.as_ref().and_then(|x| POISE_DATA.get(x)) else {
+                    ui.label("Select a weapon in the equipment view");
+                    return;
+                };
+
+                let (enemy_poise, multiplier) = enemy_poise_and_multiplier(
+                    &POISE_DATA,
+                    &self.enemy_weapon,
+                    &self.enemy_attack,
+                    self.enemy_armor_poise,
+                    self.is_enemy_in_recovery,
+                    selected_modifiers,
+                );
+
+                ui.label(format!(
+                    "Enemy Poise: {:.1}, Poise Damage Multiplier: {:.3}",
+                    enemy_poise, multiplier
+                ));
+
+                ui.collapsing("Combos", |ui| {
+                    for (name, combo) in COMBOS {
+                        let chain_poise_damage = combo_poise_damage(weapon, combo, multiplier);
+
+                        if let Some(steps) = steps_to_stagger(&chain_poise_damage, enemy_poise) {
+                            ui.label(format!("{}: {} steps", name, steps));
+                        }
+                    }
+                });
+
+                let ranking = rank_attacks(weapon, multiplier, enemy_poise);
+
+                if let Some(export) = self.table_export_controls.show(ui) {
+                    let mut table = ranking_table(weapon, &ranking)
+                        .with_metadata("Enemy Poise", format!("{:.1}", enemy_poise))
+                        .with_metadata("Poise Damage Multiplier", format!("{:.3}", multiplier));
+
+                    if let Some(enemy_weapon) = &self.enemy_weapon {
+                        table = table.with_metadata("Enemy Weapon", enemy_weapon);
+                    }
+
+                    if let Some(enemy_attack) = &self.enemy_attack {
+                        table = table.with_metadata("Enemy Attack", enemy_attack);
+                    }
+
+                    let table = table.with_loadout(equipment_view);
+
+                    self.table_export_controls
+                        .export(ui, export, "outgoing_poise", &table);
+                }
+
+                TableBuilder::new(ui)
+                    .column(Column::auto().resizable(true)) // Attack
+                    .column(Column::auto().resizable(true)) // Poise Damage
+                    .column(Column::auto().resizable(true)) // Hits
+                    .cell_layout(Layout::centered_and_justified(egui::Direction::TopDown))
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            ui.label("Attack");
+                        });
+                        header.col(|ui| {
+                            ui.label("Poise Damage");
+                        });
+                        header.col(|ui| {
+                            ui.label("Hits to Stagger");
+                        });
+                    })
+                    .body(|body| {
+                        body.rows(20.0, ranking.len(), |mut row| {
+                            let outgoing_attack = &ranking[row.index()];
+
+                            row.col(|ui| {
+                                ui.label(outgoing_attack.attack.to_string());
+                            });
+
+                            row.col(|ui| {
+                                ui.label(format!("{:.1}", outgoing_attack.poise_damage));
+                            });
+
+                            row.col(|ui| {
+                                ui.label(
+                                    outgoing_attack
+                                        .hits_to_stagger
+                                        .map(|hits| hits.to_string())
+                                        .unwrap_or("-".to_string()),
+                                );
+                            });
+                        });
+                    });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_to_stagger() {
+        assert_eq!(steps_to_stagger(&[10.0, 20.0, 30.0], 25.0), Some(2));
+        assert_eq!(steps_to_stagger(&[10.0, 20.0, 30.0], 60.0), Some(3));
+        assert_eq!(steps_to_stagger(&[10.0, 20.0, 30.0], 75.0), Some(5));
+        assert_eq!(steps_to_stagger(&[], 75.0), None);
+    }
+
+    #[test]
+    fn test_rank_attacks() {
+        let weapon = WeaponPoiseDamage::test_weapon("Claymore", "Greatsword")
+            .with_hits(|w| &mut w.one_handed.r1.chain.one, vec![40])
+            .with_hits(|w| &mut w.two_handed.r2.chain.one, vec![60, 40]);
+
+        let ranking = rank_attacks(&weapon, 0.5, 40.0);
+
+        assert_eq!(ranking.len(), 2);
+        assert_eq!(ranking[0].attack, Attacks::TwoHandedR2Chain(0));
+        assert_eq!(ranking[0].hits_to_stagger, Some(1));
+        assert_eq!(ranking[1].attack, Attacks::OneHandedR1Chain(0));
+        assert_eq!(ranking[1].hits_to_stagger, Some(2));
+    }
+}
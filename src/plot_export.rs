@@ -0,0 +1,1100 @@
+use std::{
+    fmt::{Display, Formatter, Write as _},
+    path::{Path, PathBuf},
+};
+
+use ab_glyph::{Font, FontArc, GlyphId, PxScale, ScaleFont};
+use egui::Color32;
+use egui_plot::MarkerShape;
+use strum::{Display, EnumIter, IntoEnumIterator};
+use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, Stroke, StrokeDash, Transform};
+
+use crate::{export_folder::ExportFolder, one_attack_plot_view::AxisLabels};
+
+const BACKGROUND_COLOR: Color32 = Color32::from_gray(27);
+const TEXT_COLOR: Color32 = Color32::from_gray(210);
+const GRID_COLOR: Color32 = Color32::from_gray(55);
+const FRAME_COLOR: Color32 = Color32::from_gray(100);
+
+const TITLE_SIZE: f32 = 16.0;
+const LABEL_SIZE: f32 = 12.0;
+const TICK_SIZE: f32 = 11.0;
+
+/// Rough width of a character relative to the font size, used to make room for labels
+const CHAR_WIDTH: f32 = 0.55;
+
+/// Pixels between the plot area and the image border when there is nothing else
+const PADDING: f32 = 16.0;
+
+/// Longest tick label that still gets room, longer ones run into the border
+const MAX_TICK_LABEL_WIDTH: f32 = 260.0;
+
+/// Height of a legend entry
+const LEGEND_ROW_HEIGHT: f32 = 18.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChartItem {
+    Points {
+        name: Option<String>,
+        color: Color32,
+        shape: MarkerShape,
+        radius: f32,
+        points: Vec<[f64; 2]>,
+    },
+    Line {
+        name: Option<String>,
+        color: Color32,
+        is_dashed: bool,
+        points: Vec<[f64; 2]>,
+    },
+    /// Bars from zero, each bar is its centre and height
+    Bars {
+        name: Option<String>,
+        color: Color32,
+        width: f64,
+        bars: Vec<[f64; 2]>,
+    },
+    VLine {
+        name: Option<String>,
+        color: Color32,
+        is_dashed: bool,
+        x: f64,
+    },
+    /// Unit square around `center`, e.g. a heatmap cell
+    Cell { center: [f64; 2], color: Color32 },
+}
+
+impl ChartItem {
+    fn name(&self) -> Option<&String> {
+        match self {
+            ChartItem::Points { name, .. }
+            | ChartItem::Line { name, .. }
+            | ChartItem::Bars { name, .. }
+            | ChartItem::VLine { name, .. } => name.as_ref(),
+            ChartItem::Cell { .. } => None,
+        }
+    }
+
+    fn extend_range(&self, range: &mut Range) {
+        match self {
+            ChartItem::Points { points, .. } | ChartItem::Line { points, .. } => {
+                for point in points {
+                    range.include(*point);
+                }
+            }
+            ChartItem::Bars { width, bars, .. } => {
+                for [x, height] in bars {
+                    range.include([x - width / 2.0, 0.0]);
+                    range.include([x + width / 2.0, *height]);
+                }
+            }
+            ChartItem::VLine { x, .. } => range.include_x(*x),
+            ChartItem::Cell { center: [x, y], .. } => {
+                range.include([x - 0.5, y - 0.5]);
+                range.include([x + 0.5, y + 0.5]);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AxisTicks {
+    Numeric,
+    /// Only the labelled positions get a tick, e.g. weapon names
+    Labels(AxisLabels),
+    Hidden,
+}
+
+/// Everything needed to draw a plot without egui, the plot views build it from the data they show
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chart {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub x_ticks: AxisTicks,
+    pub y_ticks: AxisTicks,
+    pub items: Vec<ChartItem>,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Chart {
+    pub fn new(
+        title: impl Into<String>,
+        x_label: impl Into<String>,
+        y_label: impl Into<String>,
+    ) -> Self {
+        Self {
+            title: title.into(),
+            x_label: x_label.into(),
+            y_label: y_label.into(),
+            x_ticks: AxisTicks::Numeric,
+            y_ticks: AxisTicks::Numeric,
+            items: Vec::new(),
+            width: 1200,
+            height: 700,
+        }
+    }
+
+    fn range(&self) -> Range {
+        let mut range = Range::default();
+
+        for item in &self.items {
+            item.extend_range(&mut range);
+        }
+
+        range
+    }
+
+    /// Horizontal line across the x range of the items added so far
+    pub fn hline(&self, name: &str, color: Color32, y: f64) -> ChartItem {
+        let (min_x, max_x) = self.range().x.unwrap_or((0.0, 1.0));
+
+        ChartItem::Line {
+            name: Some(name.to_string()),
+            color,
+            is_dashed: false,
+            points: vec![[min_x, y], [max_x, y]],
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Range {
+    x: Option<(f64, f64)>,
+    y: Option<(f64, f64)>,
+}
+
+impl Range {
+    fn include_x(&mut self, x: f64) {
+        self.x = Some(extend(self.x, x));
+    }
+
+    fn include(&mut self, [x, y]: [f64; 2]) {
+        self.x = Some(extend(self.x, x));
+        self.y = Some(extend(self.y, y));
+    }
+}
+
+fn extend(range: Option<(f64, f64)>, value: f64) -> (f64, f64) {
+    match range {
+        Some((min, max)) => (min.min(value), max.max(value)),
+        None => (value, value),
+    }
+}
+
+/// Round numbers between `min` and `max`, about one every 80 pixels
+fn numeric_ticks((min, max): (f64, f64), pixels: f32) -> Vec<f64> {
+    if max <= min {
+        return vec![min];
+    }
+
+    let raw_step = (max - min) / (pixels / 80.0).max(2.0) as f64;
+    let magnitude = 10f64.powf(raw_step.log10().floor());
+
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * magnitude)
+        .find(|step| *step >= raw_step)
+        .unwrap();
+
+    ((min / step).ceil() as i64..=(max / step).floor() as i64)
+        .map(|i| i as f64 * step)
+        .collect()
+}
+
+/// Labels inside the range, thinned out so that they don't overlap
+fn labelled_ticks(
+    axis_labels: &AxisLabels,
+    (min, max): (f64, f64),
+    pixels: f32,
+) -> Vec<(f64, &String)> {
+    let pixels_per_unit = pixels / (max - min).max(1.0) as f32;
+    let every = (TICK_SIZE * 1.3 / pixels_per_unit).ceil().max(1.0) as i64;
+
+    axis_labels
+        .range(min.ceil() as i64..=max.floor() as i64)
+        .filter(|(position, _)| position.rem_euclid(every) == 0)
+        .map(|(position, label)| (*position as f64, label))
+        .collect()
+}
+
+fn tick_text(value: f64) -> String {
+    ((value * 1000.0).round() / 1000.0).to_string()
+}
+
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().count() as f32 * size * CHAR_WIDTH
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+/// Drawing primitives in pixels, y pointing down
+trait Canvas {
+    fn polygon(&mut self, points: &[[f32; 2]], color: Color32);
+    fn polyline(&mut self, points: &[[f32; 2]], color: Color32, width: f32, is_dashed: bool);
+    /// `pos` is the vertical centre of the text, vertical text reads bottom to top
+    fn text(
+        &mut self,
+        pos: [f32; 2],
+        text: &str,
+        size: f32,
+        color: Color32,
+        anchor: Anchor,
+        is_vertical: bool,
+    );
+}
+
+fn rect(min: [f32; 2], max: [f32; 2]) -> [[f32; 2]; 4] {
+    [min, [max[0], min[1]], max, [min[0], max[1]]]
+}
+
+fn marker(
+    canvas: &mut impl Canvas,
+    [x, y]: [f32; 2],
+    radius: f32,
+    shape: MarkerShape,
+    color: Color32,
+) {
+    let polygon = |corners: &[[f32; 2]]| {
+        corners
+            .iter()
+            .map(|[dx, dy]| [x + dx * radius, y + dy * radius])
+            .collect::<Vec<_>>()
+    };
+
+    // unit vectors of the lines of the line shaped markers
+    let lines: &[[f32; 2]] = match shape {
+        MarkerShape::Circle => {
+            let corners = (0..20)
+                .map(|i| {
+                    let angle = i as f32 / 20.0 * std::f32::consts::TAU;
+                    [angle.cos(), angle.sin()]
+                })
+                .collect::<Vec<_>>();
+
+            return canvas.polygon(&polygon(&corners), color);
+        }
+        MarkerShape::Diamond => {
+            return canvas.polygon(
+                &polygon(&[[0.0, -1.0], [1.0, 0.0], [0.0, 1.0], [-1.0, 0.0]]),
+                color,
+            );
+        }
+        MarkerShape::Square => {
+            return canvas.polygon(&polygon(&rect([-0.8, -0.8], [0.8, 0.8])), color);
+        }
+        MarkerShape::Up => {
+            return canvas.polygon(&polygon(&[[0.0, -1.0], [0.87, 0.5], [-0.87, 0.5]]), color);
+        }
+        MarkerShape::Down => {
+            return canvas.polygon(&polygon(&[[0.0, 1.0], [0.87, -0.5], [-0.87, -0.5]]), color);
+        }
+        MarkerShape::Left => {
+            return canvas.polygon(&polygon(&[[-1.0, 0.0], [0.5, 0.87], [0.5, -0.87]]), color);
+        }
+        MarkerShape::Right => {
+            return canvas.polygon(&polygon(&[[1.0, 0.0], [-0.5, 0.87], [-0.5, -0.87]]), color);
+        }
+        MarkerShape::Cross => &[[0.71, 0.71], [0.71, -0.71]],
+        MarkerShape::Plus => &[[1.0, 0.0], [0.0, 1.0]],
+        MarkerShape::Asterisk => &[[0.0, 1.0], [0.87, 0.5], [0.87, -0.5]],
+    };
+
+    for [dx, dy] in lines {
+        canvas.polyline(
+            &[
+                [x - dx * radius, y - dy * radius],
+                [x + dx * radius, y + dy * radius],
+            ],
+            color,
+            1.5,
+            false,
+        );
+    }
+}
+
+/// Plot area in pixels and the data range it shows
+struct Frame {
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+    x: (f64, f64),
+    y: (f64, f64),
+}
+
+impl Frame {
+    fn new(chart: &Chart, legend_names: &[&String]) -> Self {
+        let tick_labels_width = |ticks: &AxisTicks| match ticks {
+            AxisTicks::Numeric => text_width("-000.0", TICK_SIZE),
+            AxisTicks::Labels(axis_labels) => axis_labels
+                .values()
+                .map(|label| text_width(label, TICK_SIZE))
+                .fold(0.0, f32::max)
+                .min(MAX_TICK_LABEL_WIDTH),
+            AxisTicks::Hidden => 0.0,
+        };
+
+        let x_tick_labels_height = match chart.x_ticks {
+            AxisTicks::Numeric => TICK_SIZE * 1.5,
+            _ => tick_labels_width(&chart.x_ticks),
+        };
+
+        let axis_label_size = |label: &String| match label.is_empty() {
+            true => 0.0,
+            false => LABEL_SIZE * 1.8,
+        };
+
+        let legend_width = match legend_names.is_empty() {
+            true => 0.0,
+            false => {
+                legend_names
+                    .iter()
+                    .map(|name| text_width(name, LABEL_SIZE))
+                    .fold(0.0, f32::max)
+                    + 40.0
+            }
+        };
+
+        let range = chart.range();
+
+        let pad = |range: Option<(f64, f64)>, ticks: &AxisTicks| {
+            let (min, max) = range.unwrap_or((0.0, 1.0));
+
+            match ticks {
+                AxisTicks::Numeric if max > min => {
+                    let margin = (max - min) * 0.05;
+                    (min - margin, max + margin)
+                }
+                AxisTicks::Numeric => (min - 1.0, max + 1.0),
+                _ => (min, max.max(min + 1.0)),
+            }
+        };
+
+        Self {
+            left: PADDING
+                + axis_label_size(&chart.y_label)
+                + tick_labels_width(&chart.y_ticks)
+                + 6.0,
+            top: PADDING + TITLE_SIZE * 2.0,
+            right: chart.width as f32 - PADDING - legend_width,
+            bottom: chart.height as f32
+                - PADDING
+                - axis_label_size(&chart.x_label)
+                - x_tick_labels_height
+                - 6.0,
+            x: pad(range.x, &chart.x_ticks),
+            y: pad(range.y, &chart.y_ticks),
+        }
+    }
+
+    fn to_screen(&self, [x, y]: [f64; 2]) -> [f32; 2] {
+        [
+            self.left + ((x - self.x.0) / (self.x.1 - self.x.0)) as f32 * (self.right - self.left),
+            self.bottom
+                - ((y - self.y.0) / (self.y.1 - self.y.0)) as f32 * (self.bottom - self.top),
+        ]
+    }
+}
+
+fn render(chart: &Chart, canvas: &mut impl Canvas) {
+    let width = chart.width as f32;
+    let height = chart.height as f32;
+
+    let mut legend_items = Vec::<&ChartItem>::new();
+
+    for item in &chart.items {
+        if let Some(name) = item.name() {
+            if legend_items.iter().all(|other| other.name() != Some(name)) {
+                legend_items.push(item);
+            }
+        }
+    }
+
+    let legend_names = legend_items
+        .iter()
+        .filter_map(|item| item.name())
+        .collect::<Vec<_>>();
+
+    let frame = Frame::new(chart, &legend_names);
+
+    canvas.polygon(&rect([0.0, 0.0], [width, height]), BACKGROUND_COLOR);
+
+    canvas.text(
+        [width / 2.0, PADDING + TITLE_SIZE / 2.0],
+        &chart.title,
+        TITLE_SIZE,
+        TEXT_COLOR,
+        Anchor::Middle,
+        false,
+    );
+
+    let x_ticks = match &chart.x_ticks {
+        AxisTicks::Numeric => numeric_ticks(frame.x, frame.right - frame.left)
+            .into_iter()
+            .map(|x| (x, tick_text(x)))
+            .collect(),
+        AxisTicks::Labels(axis_labels) => {
+            labelled_ticks(axis_labels, frame.x, frame.right - frame.left)
+                .into_iter()
+                .map(|(x, label)| (x, label.clone()))
+                .collect()
+        }
+        AxisTicks::Hidden => Vec::new(),
+    };
+
+    for (x, label) in x_ticks {
+        let [screen_x, _] = frame.to_screen([x, frame.y.0]);
+
+        canvas.polyline(
+            &[[screen_x, frame.top], [screen_x, frame.bottom]],
+            GRID_COLOR,
+            1.0,
+            false,
+        );
+
+        match chart.x_ticks {
+            AxisTicks::Numeric => canvas.text(
+                [screen_x, frame.bottom + 4.0 + TICK_SIZE / 2.0],
+                &label,
+                TICK_SIZE,
+                TEXT_COLOR,
+                Anchor::Middle,
+                false,
+            ),
+            _ => canvas.text(
+                [screen_x, frame.bottom + 4.0],
+                &label,
+                TICK_SIZE,
+                TEXT_COLOR,
+                Anchor::End,
+                true,
+            ),
+        }
+    }
+
+    let y_ticks = match &chart.y_ticks {
+        AxisTicks::Numeric => numeric_ticks(frame.y, frame.bottom - frame.top)
+            .into_iter()
+            .map(|y| (y, tick_text(y)))
+            .collect(),
+        AxisTicks::Labels(axis_labels) => {
+            labelled_ticks(axis_labels, frame.y, frame.bottom - frame.top)
+                .into_iter()
+                .map(|(y, label)| (y, label.clone()))
+                .collect()
+        }
+        AxisTicks::Hidden => Vec::new(),
+    };
+
+    for (y, label) in y_ticks {
+        let [_, screen_y] = frame.to_screen([frame.x.0, y]);
+
+        canvas.polyline(
+            &[[frame.left, screen_y], [frame.right, screen_y]],
+            GRID_COLOR,
+            1.0,
+            false,
+        );
+        canvas.text(
+            [frame.left - 6.0, screen_y],
+            &label,
+            TICK_SIZE,
+            TEXT_COLOR,
+            Anchor::End,
+            false,
+        );
+    }
+
+    for item in &chart.items {
+        match item {
+            ChartItem::Points {
+                color,
+                shape,
+                radius,
+                points,
+                ..
+            } => {
+                for point in points {
+                    marker(canvas, frame.to_screen(*point), *radius, *shape, *color);
+                }
+            }
+            ChartItem::Line {
+                color,
+                is_dashed,
+                points,
+                ..
+            } => {
+                let points = points
+                    .iter()
+                    .map(|point| frame.to_screen(*point))
+                    .collect::<Vec<_>>();
+
+                canvas.polyline(&points, *color, 1.5, *is_dashed);
+            }
+            ChartItem::Bars {
+                color, width, bars, ..
+            } => {
+                for [x, bar_height] in bars {
+                    let min = frame.to_screen([x - width / 2.0, *bar_height]);
+                    let max = frame.to_screen([x + width / 2.0, 0.0]);
+
+                    canvas.polygon(&rect(min, max), *color);
+                    canvas.polyline(&rect(min, max), BACKGROUND_COLOR, 1.0, false);
+                }
+            }
+            ChartItem::VLine {
+                color,
+                is_dashed,
+                x,
+                ..
+            } => {
+                let [screen_x, _] = frame.to_screen([*x, frame.y.0]);
+
+                canvas.polyline(
+                    &[[screen_x, frame.top], [screen_x, frame.bottom]],
+                    *color,
+                    1.5,
+                    *is_dashed,
+                );
+            }
+            ChartItem::Cell {
+                center: [x, y],
+                color,
+            } => {
+                let min = frame.to_screen([x - 0.5, y + 0.5]);
+                let max = frame.to_screen([x + 0.5, y - 0.5]);
+
+                canvas.polygon(&rect(min, [max[0] - 1.0, max[1] - 1.0]), *color);
+            }
+        }
+    }
+
+    let mut frame_outline = rect([frame.left, frame.top], [frame.right, frame.bottom]).to_vec();
+    frame_outline.push(frame_outline[0]);
+    canvas.polyline(&frame_outline, FRAME_COLOR, 1.0, false);
+
+    canvas.text(
+        [
+            (frame.left + frame.right) / 2.0,
+            height - PADDING - LABEL_SIZE / 2.0,
+        ],
+        &chart.x_label,
+        LABEL_SIZE,
+        TEXT_COLOR,
+        Anchor::Middle,
+        false,
+    );
+    canvas.text(
+        [PADDING + LABEL_SIZE / 2.0, (frame.top + frame.bottom) / 2.0],
+        &chart.y_label,
+        LABEL_SIZE,
+        TEXT_COLOR,
+        Anchor::Middle,
+        true,
+    );
+
+    for (row, item) in legend_items.iter().enumerate() {
+        let x = frame.right + 12.0;
+        let y = frame.top + LEGEND_ROW_HEIGHT * (row as f32 + 0.5);
+
+        match item {
+            ChartItem::Points {
+                color,
+                shape,
+                radius,
+                ..
+            } => marker(canvas, [x + 8.0, y], radius.clamp(3.0, 5.0), *shape, *color),
+            ChartItem::Line {
+                color, is_dashed, ..
+            }
+            | ChartItem::VLine {
+                color, is_dashed, ..
+            } => canvas.polyline(&[[x, y], [x + 16.0, y]], *color, 1.5, *is_dashed),
+            ChartItem::Bars { color, .. } => {
+                canvas.polygon(&rect([x + 2.0, y - 5.0], [x + 14.0, y + 5.0]), *color)
+            }
+            ChartItem::Cell { .. } => {}
+        }
+
+        canvas.text(
+            [x + 22.0, y],
+            item.name().unwrap(),
+            LABEL_SIZE,
+            TEXT_COLOR,
+            Anchor::Start,
+            false,
+        );
+    }
+}
+
+struct SvgCanvas {
+    svg: String,
+}
+
+/// Colour as an attribute, e.g. `fill` or `stroke`, and its opacity
+fn svg_color(attribute: &str, color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+
+    format!(
+        "{0}=\"#{1:02x}{2:02x}{3:02x}\" {0}-opacity=\"{4:.3}\"",
+        attribute,
+        r,
+        g,
+        b,
+        a as f32 / 255.0
+    )
+}
+
+fn svg_points(points: &[[f32; 2]]) -> String {
+    points
+        .iter()
+        .map(|[x, y]| format!("{:.1},{:.1}", x, y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+impl Canvas for SvgCanvas {
+    fn polygon(&mut self, points: &[[f32; 2]], color: Color32) {
+        writeln!(
+            self.svg,
+            "<polygon points=\"{}\" {}/>",
+            svg_points(points),
+            svg_color("fill", color)
+        )
+        .unwrap();
+    }
+
+    fn polyline(&mut self, points: &[[f32; 2]], color: Color32, width: f32, is_dashed: bool) {
+        writeln!(
+            self.svg,
+            "<polyline points=\"{}\" fill=\"none\" {} stroke-width=\"{}\"{}/>",
+            svg_points(points),
+            svg_color("stroke", color),
+            width,
+            match is_dashed {
+                true => " stroke-dasharray=\"6 4\"",
+                false => "",
+            }
+        )
+        .unwrap();
+    }
+
+    fn text(
+        &mut self,
+        [x, y]: [f32; 2],
+        text: &str,
+        size: f32,
+        color: Color32,
+        anchor: Anchor,
+        is_vertical: bool,
+    ) {
+        if text.is_empty() {
+            return;
+        }
+
+        let text_anchor = match anchor {
+            Anchor::Start => "start",
+            Anchor::Middle => "middle",
+            Anchor::End => "end",
+        };
+
+        let transform = match is_vertical {
+            true => format!(" transform=\"rotate(-90 {:.1} {:.1})\"", x, y),
+            false => String::new(),
+        };
+
+        writeln!(
+            self.svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" font-family=\"sans-serif\" font-size=\"{}\" {} text-anchor=\"{}\" dominant-baseline=\"central\"{}>{}</text>",
+            x,
+            y,
+            size,
+            svg_color("fill", color),
+            text_anchor,
+            transform,
+            escape_xml(text)
+        )
+        .unwrap();
+    }
+}
+
+struct PngCanvas {
+    pixmap: Pixmap,
+    font: FontArc,
+}
+
+fn paint(color: Color32) -> Paint<'static> {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(r, g, b, a);
+    paint.anti_alias = true;
+    paint
+}
+
+fn path(points: &[[f32; 2]], is_closed: bool) -> Option<tiny_skia::Path> {
+    let (first, rest) = points.split_first()?;
+
+    let mut path_builder = PathBuilder::new();
+    path_builder.move_to(first[0], first[1]);
+
+    for [x, y] in rest {
+        path_builder.line_to(*x, *y);
+    }
+
+    if is_closed {
+        path_builder.close();
+    }
+
+    path_builder.finish()
+}
+
+impl PngCanvas {
+    /// Blends `color` into the pixel at `x`, `y` with the given coverage
+    fn blend(&mut self, x: f32, y: f32, color: Color32, coverage: f32) {
+        let (x, y) = (x.round() as i64, y.round() as i64);
+
+        if x < 0 || y < 0 || x >= self.pixmap.width() as i64 || y >= self.pixmap.height() as i64 {
+            return;
+        }
+
+        let index = (y as usize * self.pixmap.width() as usize + x as usize) * 4;
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        let alpha = coverage.clamp(0.0, 1.0) * a as f32 / 255.0;
+
+        // premultiplied, so the colour is scaled by alpha as well
+        let pixel = &mut self.pixmap.data_mut()[index..index + 4];
+
+        for (channel, value) in pixel.iter_mut().zip([r, g, b, 255]) {
+            *channel = (*channel as f32 * (1.0 - alpha) + value as f32 * alpha).round() as u8;
+        }
+    }
+}
+
+impl Canvas for PngCanvas {
+    fn polygon(&mut self, points: &[[f32; 2]], color: Color32) {
+        if let Some(path) = path(points, true) {
+            self.pixmap.fill_path(
+                &path,
+                &paint(color),
+                FillRule::Winding,
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+
+    fn polyline(&mut self, points: &[[f32; 2]], color: Color32, width: f32, is_dashed: bool) {
+        let stroke = Stroke {
+            width,
+            dash: match is_dashed {
+                true => StrokeDash::new(vec![6.0, 4.0], 0.0),
+                false => None,
+            },
+            ..Default::default()
+        };
+
+        if let Some(path) = path(points, false) {
+            self.pixmap
+                .stroke_path(&path, &paint(color), &stroke, Transform::identity(), None);
+        }
+    }
+
+    fn text(
+        &mut self,
+        [x, y]: [f32; 2],
+        text: &str,
+        size: f32,
+        color: Color32,
+        anchor: Anchor,
+        is_vertical: bool,
+    ) {
+        let font = self.font.clone();
+        let scaled_font = font.as_scaled(PxScale::from(size));
+
+        let mut caret = 0.0;
+        let mut previous = None::<GlyphId>;
+        let mut glyphs = Vec::new();
+
+        for c in text.chars() {
+            let glyph_id = scaled_font.glyph_id(c);
+
+            if let Some(previous) = previous {
+                caret += scaled_font.kern(previous, glyph_id);
+            }
+
+            glyphs.push(
+                glyph_id
+                    .with_scale_and_position(size, ab_glyph::point(caret, scaled_font.ascent())),
+            );
+
+            caret += scaled_font.h_advance(glyph_id);
+            previous = Some(glyph_id);
+        }
+
+        let offset_x = match anchor {
+            Anchor::Start => 0.0,
+            Anchor::Middle => -caret / 2.0,
+            Anchor::End => -caret,
+        };
+        let offset_y = -(scaled_font.ascent() - scaled_font.descent()) / 2.0;
+
+        for glyph in glyphs {
+            let Some(outlined_glyph) = font.outline_glyph(glyph) else {
+                continue;
+            };
+
+            let bounds = outlined_glyph.px_bounds();
+
+            outlined_glyph.draw(|glyph_x, glyph_y, coverage| {
+                let local_x = bounds.min.x + glyph_x as f32 + offset_x;
+                let local_y = bounds.min.y + glyph_y as f32 + offset_y;
+
+                // turned a quarter counterclockwise
+                let (pixel_x, pixel_y) = match is_vertical {
+                    true => (x + local_y, y - local_x),
+                    false => (x + local_x, y + local_y),
+                };
+
+                self.blend(pixel_x, pixel_y, color, coverage);
+            });
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumIter, Display)]
+pub enum ExportFormat {
+    #[strum(to_string = "SVG")]
+    Svg,
+    #[strum(to_string = "PNG")]
+    Png,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Svg => "svg",
+            ExportFormat::Png => "png",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+
+        ExportFormat::iter().find(|format| format.extension() == extension)
+    }
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    UnknownFormat(PathBuf),
+    Render(String),
+    Io(std::io::Error),
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::UnknownFormat(path) => {
+                write!(f, "{} is neither .svg nor .png", path.display())
+            }
+            ExportError::Render(error) => write!(f, "Rendering failed: {}", error),
+            ExportError::Io(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<std::io::Error> for ExportError {
+    fn from(error: std::io::Error) -> Self {
+        ExportError::Io(error)
+    }
+}
+
+pub fn to_svg(chart: &Chart) -> String {
+    let mut canvas = SvgCanvas {
+        svg: format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+            chart.width, chart.height
+        ),
+    };
+
+    render(chart, &mut canvas);
+
+    canvas.svg.push_str("</svg>\n");
+    canvas.svg
+}
+
+/// Rasterised on the CPU, text uses egui's default font
+pub fn to_png(chart: &Chart) -> Result<Vec<u8>, ExportError> {
+    let pixmap = Pixmap::new(chart.width, chart.height)
+        .ok_or_else(|| ExportError::Render("Empty image".to_string()))?;
+
+    let font_data = egui::FontDefinitions::default()
+        .font_data
+        .remove("Ubuntu-Light")
+        .ok_or_else(|| ExportError::Render("Missing font".to_string()))?;
+
+    let font = FontArc::try_from_vec(font_data.font.into_owned())
+        .map_err(|error| ExportError::Render(error.to_string()))?;
+
+    let mut canvas = PngCanvas { pixmap, font };
+
+    render(chart, &mut canvas);
+
+    canvas
+        .pixmap
+        .encode_png()
+        .map_err(|error| ExportError::Render(error.to_string()))
+}
+
+/// The format is taken from the extension of `path`
+pub fn save(chart: &Chart, path: &Path) -> Result<(), ExportError> {
+    let bytes = match ExportFormat::from_path(path) {
+        Some(ExportFormat::Svg) => to_svg(chart).into_bytes(),
+        Some(ExportFormat::Png) => to_png(chart)?,
+        None => return Err(ExportError::UnknownFormat(path.to_path_buf())),
+    };
+
+    std::fs::write(path, bytes)?;
+
+    log::info!("Exported {} to {}", chart.title, path.display());
+
+    Ok(())
+}
+
+/// Export buttons of a plot view, the export folder and the outcome of the last export
+#[derive(Default)]
+pub struct ExportControls {
+    folder: ExportFolder,
+}
+
+impl ExportControls {
+    /// Returns the format of the clicked button, if any
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<ExportFormat> {
+        let mut clicked_format = None;
+
+        ui.horizontal_wrapped(|ui| {
+            self.folder.show_folder(ui);
+
+            for format in ExportFormat::iter() {
+                ui.button(format!("Export {}", format))
+                    .clicked()
+                    .then(|| clicked_format = Some(format));
+            }
+
+            self.folder.show_last_export(ui);
+        });
+
+        clicked_format
+    }
+
+    /// Saves every chart to a new `<file stem>.<extension>` in the export folder
+    pub fn export(&mut self, format: ExportFormat, charts: &[(&str, Chart)]) {
+        let mut saved = Vec::new();
+
+        for (file_stem, chart) in charts {
+            let path = self.folder.new_path(file_stem, format.extension());
+
+            match save(chart, &path) {
+                Ok(()) => saved.push(path.display().to_string()),
+                Err(error) => {
+                    log::warn!("Failed to export {}: {}", path.display(), error);
+                    self.folder
+                        .set_last_export(format!("Export failed: {}", error));
+                    return;
+                }
+            }
+        }
+
+        self.folder
+            .set_last_export(format!("Saved {}", saved.join(", ")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart() -> Chart {
+        let mut chart = Chart::new("Claymore & Co", "Weapon", "Poise Damage");
+
+        chart.items.push(ChartItem::Points {
+            name: Some("Greatsword".to_string()),
+            color: Color32::from_rgb(200, 100, 50),
+            shape: MarkerShape::Diamond,
+            radius: 3.0,
+            points: vec![[0.0, 60.0], [1.0, 75.0]],
+        });
+        chart
+            .items
+            .push(chart.hline("Armor Poise", Color32::RED, 51.0));
+
+        chart
+    }
+
+    #[test]
+    fn test_numeric_ticks() {
+        assert_eq!(
+            numeric_ticks((0.0, 100.0), 400.0),
+            vec![0.0, 20.0, 40.0, 60.0, 80.0, 100.0]
+        );
+        assert_eq!(numeric_ticks((3.0, 3.0), 400.0), vec![3.0]);
+    }
+
+    #[test]
+    fn test_labelled_ticks() {
+        let axis_labels = (0..100)
+            .map(|i| (i, format!("Weapon {}", i)))
+            .collect::<AxisLabels>();
+
+        let ticks = labelled_ticks(&axis_labels, (10.0, 60.0), 1000.0);
+        assert_eq!(ticks.len(), 51);
+
+        let ticks = labelled_ticks(&axis_labels, (0.0, 99.0), 200.0);
+        assert!(ticks.len() < 20);
+        assert_eq!(ticks[0], (0.0, &"Weapon 0".to_string()));
+    }
+
+    #[test]
+    fn test_to_svg() {
+        let svg = to_svg(&chart());
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("Claymore &amp; Co"));
+        assert!(svg.contains(">Greatsword</text>"));
+        assert!(svg.contains(">Armor Poise</text>"));
+    }
+
+    #[test]
+    fn test_to_png() {
+        let png = to_png(&chart()).unwrap();
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    }
+
+    #[test]
+    fn test_export_format() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("plot.PNG")),
+            Some(ExportFormat::Png)
+        );
+        assert_eq!(ExportFormat::from_path(Path::new("plot.jpg")), None);
+    }
+}
@@ -1,9 +1,20 @@
+use serde::{Deserialize, Serialize};
+
 use crate::{
     data::{ApplyMultiplier, WeaponPoiseDamage},
+    equipment_view::{effective_poise, EquipmentView},
+    selection::{SelectionBus, SelectionEvent},
     static_data::POISE_DATA,
+    table_export::TableExportControls,
     weapon_select_view::WeaponSelectView,
 };
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PoiseDataViewState {
+    pub is_open: bool,
+    pub selected_weapon: Option<String>,
+}
+
 #[derive(Default)]
 pub struct PoiseDataView {
     pub is_open: bool,
@@ -12,6 +23,7 @@ pub struct PoiseDataView {
     pub selected_weapon_class: Option<String>,
     pub selected_poise_damage: Option<WeaponPoiseDamage>,
     pub weapon_select_view: WeaponSelectView,
+    table_export_controls: TableExportControls,
 }
 
 impl PoiseDataView {
@@ -25,10 +37,48 @@ impl PoiseDataView {
             selected_weapon_class: None,
             selected_poise_damage: None,
             weapon_select_view,
+            table_export_controls: TableExportControls::default(),
         }
     }
 
-    pub fn show(&mut self, ui: &mut egui::Ui, incoming_poise_damage_multiplier: &Option<f64>) {
+    pub fn state(&self) -> PoiseDataViewState {
+        PoiseDataViewState {
+            is_open: self.is_open,
+            selected_weapon: self.selected_weapon.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, state: PoiseDataViewState) {
+        self.is_open = state.is_open;
+        self.selected_weapon = state
+            .selected_weapon
+            .filter(|weapon| POISE_DATA.contains_key(weapon));
+    }
+
+    /// Weapons selected in other views are loaded and shown
+    pub fn on_selection_event(&mut self, event: &SelectionEvent) {
+        if let SelectionEvent::WeaponSelected(weapon) = event {
+            if POISE_DATA.contains_key(weapon) && self.selected_weapon.as_ref() != Some(weapon) {
+                self.selected_weapon = Some(weapon.clone());
+                self.is_selected_weapon_change = true;
+                self.is_open = true;
+            }
+        }
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        equipment_view: &EquipmentView,
+        selection_bus: &mut SelectionBus,
+    ) {
+        let EquipmentView {
+            incoming_poise_damage_multiplier,
+            hyperarmor,
+            armor_poise,
+            ..
+        } = equipment_view;
+
         egui::Window::new("Weapon Poise Damage Data")
             .id("Weapon Poise Damage Data Window".into())
             .resizable(true)
@@ -43,10 +93,11 @@ impl PoiseDataView {
                     self.weapon_select_view.show(ui, "Incoming Poise Damage");
                 }
 
-                if let Some(selected_weapon) = &self.weapon_select_view.selected_weapon {
+                if let Some(selected_weapon) = self.weapon_select_view.selected_weapon.take() {
                     self.selected_weapon = Some(selected_weapon.clone());
-                    self.weapon_select_view.selected_weapon = None;
                     self.is_selected_weapon_change = true;
+
+                    selection_bus.publish(SelectionEvent::WeaponSelected(selected_weapon));
                 }
 
                 if let Some(weapon) = &self.selected_weapon {
@@ -65,8 +116,29 @@ impl PoiseDataView {
                     }
                 }
 
+                let poise = match hyperarmor.is_some() || *armor_poise > 0 {
+                    true => Some(effective_poise(
+                        hyperarmor.unwrap_or_default(),
+                        *armor_poise,
+                    )),
+                    false => None,
+                };
+
                 if let Some(selected_poise_damage) = &self.selected_poise_damage {
-                    selected_poise_damage.view(ui);
+                    if let Some(export) = self.table_export_controls.show(ui) {
+                        let table = selected_poise_damage
+                            .table(poise)
+                            .with_loadout(equipment_view);
+
+                        self.table_export_controls.export(
+                            ui,
+                            export,
+                            "weapon_poise_damage",
+                            &table,
+                        );
+                    }
+
+                    selected_poise_damage.view(ui, poise);
                 }
             });
     }
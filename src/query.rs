@@ -0,0 +1,648 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+use crate::{
+    data::{ApplyMultiplier, Attacks, WeaponPoiseDamage},
+    equipment_view::{effective_poise, hits_to_stagger, EquipmentView, EquipmentViewState},
+    loadout_code::LoadoutCode,
+    modifiers::Modifier,
+    static_data::{INNATE_WEAPON_POISE, MODIFIERS, POISE_DATA, WEAPON_CLASSES},
+    table_export::Table,
+};
+
+pub const BULL_GOAT: &str = "Bull-Goat's Talisman";
+
+/// Weapons listed when a name is ambiguous or unknown
+const MAX_SUGGESTIONS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryError {
+    UnknownWeapon(String),
+    AmbiguousWeapon(String, Vec<String>),
+    UnknownAttack(String),
+    UnknownWeaponClass(String),
+    UnknownModifier(String),
+    InvalidLoadoutCode(String),
+    MissingWeapon,
+    MissingAttack,
+    WeaponWithoutAttack(String, Attacks),
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::UnknownWeapon(name) => write!(f, "Unknown weapon {}", name),
+            QueryError::AmbiguousWeapon(name, weapons) => {
+                write!(f, "{} matches {}", name, weapons.join(", "))
+            }
+            QueryError::UnknownAttack(name) => write!(f, "Unknown attack {}", name),
+            QueryError::UnknownWeaponClass(name) => write!(f, "Unknown weapon class {}", name),
+            QueryError::UnknownModifier(name) => write!(f, "Unknown modifier {}", name),
+            QueryError::InvalidLoadoutCode(error) => write!(f, "Invalid loadout code: {}", error),
+            QueryError::MissingWeapon => write!(f, "No weapon given"),
+            QueryError::MissingAttack => write!(f, "No attack given"),
+            QueryError::WeaponWithoutAttack(weapon, attack) => {
+                write!(f, "{} has no {}", weapon, attack)
+            }
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Exact name first, then ignoring case, then the only weapon containing `name`
+pub fn find_weapon<'a>(
+    poise_data: &'a BTreeMap<String, WeaponPoiseDamage>,
+    name: &str,
+) -> Result<&'a String, QueryError> {
+    if let Some((weapon, _)) = poise_data.get_key_value(name) {
+        return Ok(weapon);
+    }
+
+    let lowercase_name = name.trim().to_lowercase();
+
+    if let Some(weapon) = poise_data
+        .keys()
+        .find(|weapon| weapon.to_lowercase() == lowercase_name)
+    {
+        return Ok(weapon);
+    }
+
+    let matches = poise_data
+        .keys()
+        .filter(|weapon| weapon.to_lowercase().contains(&lowercase_name))
+        .collect::<Vec<_>>();
+
+    match matches.as_slice() {
+        [] => Err(QueryError::UnknownWeapon(name.to_string())),
+        [weapon] => Ok(weapon),
+        _ => Err(QueryError::AmbiguousWeapon(
+            name.to_string(),
+            matches.into_iter().take(MAX_SUGGESTIONS).cloned().collect(),
+        )),
+    }
+}
+
+pub fn find_attack(name: &str) -> Result<Attacks, QueryError> {
+    Attacks::from_name(name).ok_or_else(|| QueryError::UnknownAttack(name.to_string()))
+}
+
+pub fn find_weapon_class(name: &str) -> Result<&'static String, QueryError> {
+    WEAPON_CLASSES
+        .keys()
+        .find(|weapon_class| weapon_class.to_lowercase() == name.trim().to_lowercase())
+        .ok_or_else(|| QueryError::UnknownWeaponClass(name.to_string()))
+}
+
+/// Modifier of the registry, ignoring case
+pub fn find_modifier(name: &str) -> Result<&'static Modifier, QueryError> {
+    MODIFIERS
+        .iter()
+        .find(|modifier| modifier.name.to_lowercase() == name.to_lowercase())
+        .ok_or_else(|| QueryError::UnknownModifier(name.to_string()))
+}
+
+/// A loadout described by a loadout code and/or its fields, the fields take precedence
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LoadoutQuery {
+    pub code: Option<String>,
+    pub weapon: Option<String>,
+    pub attack: Option<String>,
+    pub armor_poise: Option<u16>,
+    pub modifiers: Vec<String>,
+    pub is_bull_goat: bool,
+    pub is_in_recovery: bool,
+}
+
+impl LoadoutQuery {
+    pub fn equipment_view(&self) -> Result<EquipmentView, QueryError> {
+        let mut state = match &self.code {
+            Some(code) => LoadoutCode::decode(code)
+                .map_err(|error| QueryError::InvalidLoadoutCode(error.to_string()))?
+                .into_state(),
+            None => EquipmentView::new().state(),
+        };
+
+        if let Some(weapon) = &self.weapon {
+            state.selected_weapon = Some(find_weapon(&POISE_DATA, weapon)?.clone());
+        }
+
+        if let Some(attack) = &self.attack {
+            state.selected_attack = Some(find_attack(attack)?);
+        }
+
+        if let Some(armor_poise) = self.armor_poise {
+            state.armor_poise = armor_poise;
+        }
+
+        // Bull-Goat's Talisman comes from modifiers.csv like every other modifier
+        let bull_goat = self.is_bull_goat.then(|| BULL_GOAT.to_string());
+
+        for modifier in self.modifiers.iter().chain(&bull_goat) {
+            state
+                .selected_modifiers
+                .insert(find_modifier(modifier)?.name.clone());
+        }
+
+        state.is_in_recovery |= self.is_in_recovery;
+
+        let mut equipment_view = EquipmentView::new();
+        equipment_view.restore(EquipmentViewState {
+            is_open: false,
+            ..state
+        });
+
+        Ok(equipment_view)
+    }
+}
+
+/// The poise table of the weapon data window, scaled by the loadout's multiplier
+pub fn weapon_table(weapon: &str, equipment_view: &EquipmentView) -> Result<Table, QueryError> {
+    let weapon = find_weapon(&POISE_DATA, weapon)?;
+    let weapon_poise_damage = &POISE_DATA[weapon];
+
+    let weapon_poise_damage = match equipment_view.incoming_poise_damage_multiplier {
+        Some(multiplier) => weapon_poise_damage.apply_multiplier(multiplier),
+        None => weapon_poise_damage.clone(),
+    };
+
+    let poise = match equipment_view.hyperarmor.is_some() || equipment_view.armor_poise > 0 {
+        true => Some(effective_poise(
+            equipment_view.hyperarmor.unwrap_or_default(),
+            equipment_view.armor_poise,
+        )),
+        false => None,
+    };
+
+    Ok(weapon_poise_damage
+        .table(poise)
+        .with_metadata("Class", &weapon_poise_damage.class)
+        .with_loadout(equipment_view))
+}
+
+/// Hyperarmor of the loadout's weapon and attack, a single row
+pub fn hyperarmor_table(equipment_view: &EquipmentView) -> Result<Table, QueryError> {
+    let state = equipment_view.state();
+
+    let weapon = state.selected_weapon.ok_or(QueryError::MissingWeapon)?;
+    let attack = state.selected_attack.ok_or(QueryError::MissingAttack)?;
+
+    let weapon_poise_damage = &POISE_DATA[&weapon];
+
+    if weapon_poise_damage
+        .get_poise_damage_by_attack(&attack)
+        .filter(|poise_damage_values| !poise_damage_values.is_empty())
+        .is_none()
+    {
+        return Err(QueryError::WeaponWithoutAttack(weapon, attack));
+    }
+
+    let hyperarmor = equipment_view.hyperarmor.unwrap_or_default();
+
+    let mut table = Table::new(
+        format!("{} - {}", weapon, attack),
+        &[
+            "Weapon",
+            "Class",
+            "Attack",
+            "Innate Weapon Poise",
+            "Weapon Hyperarmor",
+            "Armor Poise",
+            "Hyperarmor",
+            "Poise",
+            "Incoming Poise Damage Multiplier",
+        ],
+    )
+    .with_metadata(
+        "Loadout Code",
+        LoadoutCode::from_state(&equipment_view.state()).encode(),
+    );
+
+    table.push_row(vec![
+        weapon.clone(),
+        weapon_poise_damage.class.clone(),
+        attack.to_string(),
+        INNATE_WEAPON_POISE
+            .get(&weapon)
+            .copied()
+            .unwrap_or_default()
+            .to_string(),
+        format!(
+            "{:.1}",
+            equipment_view.weapon_hyperarmor.unwrap_or_default()
+        ),
+        equipment_view.armor_poise.to_string(),
+        format!("{:.1}", hyperarmor),
+        format!(
+            "{:.1}",
+            effective_poise(hyperarmor, equipment_view.armor_poise)
+        ),
+        format!(
+            "{:.3}",
+            equipment_view
+                .incoming_poise_damage_multiplier
+                .unwrap_or(1.0)
+        ),
+    ]);
+
+    Ok(table)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaggeringAttack {
+    pub weapon: String,
+    pub class: String,
+    pub attack: Attacks,
+    pub poise_damage: f64,
+    /// Poise damage left over after breaking `poise` with a single hit, negative if it takes more
+    pub margin: f64,
+    pub hits_to_stagger: u32,
+}
+
+/// Every attack of `weapons` that breaks `poise` within `max_hits`
+pub fn staggers_me<'a>(
+    weapons: impl IntoIterator<Item = &'a WeaponPoiseDamage>,
+    incoming_poise_damage_multiplier: f64,
+    poise: f64,
+    max_hits: u32,
+) -> Vec<StaggeringAttack> {
+    weapons
+        .into_iter()
+        .flat_map(|weapon| {
+            weapon
+                .attacks()
+                .filter_map(|(attack, _)| {
+                    // truncated per hit like in the plots
+                    let poise_damage = weapon
+                        .get_poise_damage_by_attack_w_multiplier(
+                            &attack,
+                            incoming_poise_damage_multiplier,
+                        )?
+                        .total() as f64;
+
+                    let hits_to_stagger = hits_to_stagger(poise_damage, poise)?;
+
+                    match hits_to_stagger <= max_hits {
+                        true => Some(StaggeringAttack {
+                            weapon: weapon.name.clone(),
+                            class: weapon.class.clone(),
+                            attack,
+                            poise_damage,
+                            margin: poise_damage - poise,
+                            hits_to_stagger,
+                        }),
+                        false => None,
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Same columns and formatting as the window
+pub fn staggers_me_table<'a>(
+    staggering_attacks: impl IntoIterator<Item = &'a StaggeringAttack>,
+    poise: f64,
+) -> Table {
+    let mut table = Table::new(
+        "What Staggers Me",
+        &[
+            "Weapon",
+            "Class",
+            "Attack",
+            "Poise Damage",
+            "Margin",
+            "Hits",
+        ],
+    )
+    .with_metadata("Poise", format!("{:.1}", poise));
+
+    for staggering_attack in staggering_attacks {
+        table.push_row(vec![
+            staggering_attack.weapon.clone(),
+            staggering_attack.class.clone(),
+            staggering_attack.attack.to_string(),
+            format!("{:.1}", staggering_attack.poise_damage),
+            format!("{:+.1}", staggering_attack.margin),
+            staggering_attack.hits_to_stagger.to_string(),
+        ]);
+    }
+
+    table
+}
+
+/// The "What Staggers Me" report of the loadout, largest margin first
+pub fn staggers_me_report(
+    equipment_view: &EquipmentView,
+    max_hits: u32,
+    weapon_class: Option<&str>,
+) -> Result<Table, QueryError> {
+    let weapon_class = weapon_class.map(find_weapon_class).transpose()?;
+
+    let poise = effective_poise(
+        equipment_view.hyperarmor.unwrap_or_default(),
+        equipment_view.armor_poise,
+    );
+
+    let mut report = staggers_me(
+        POISE_DATA.values(),
+        equipment_view
+            .incoming_poise_damage_multiplier
+            .unwrap_or(1.0),
+        poise,
+        max_hits,
+    );
+
+    report.retain(|staggering_attack| match weapon_class {
+        Some(weapon_class) => &staggering_attack.class == weapon_class,
+        None => true,
+    });
+    report.sort_by(|a, b| b.margin.total_cmp(&a.margin));
+
+    let mut table = staggers_me_table(&report, poise).with_metadata("Max Hits", max_hits);
+
+    if let Some(weapon_class) = weapon_class {
+        table = table.with_metadata("Weapon Class", weapon_class);
+    }
+
+    Ok(table.with_loadout(equipment_view))
+}
+
+/// A weapon's poise damage as plotted, `x` is its position within its weapon class
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeaponPoint {
+    pub weapon: String,
+    pub weapon_class: String,
+    pub attack: Attacks,
+    pub x: f64,
+    pub hits: Vec<f64>,
+    pub total: f64,
+}
+
+/// Weapons without poise damage for the attack are left out
+pub fn weapon_points_for_attack(
+    poise_data: &BTreeMap<String, WeaponPoiseDamage>,
+    attack: &Attacks,
+    incoming_poise_damage_multiplier: &Option<f64>,
+) -> BTreeMap<String, Vec<WeaponPoint>> {
+    let mut weapon_points_by_class = BTreeMap::<String, Vec<WeaponPoint>>::new();
+
+    for (weapon, weapon_poise_damage) in poise_data.iter() {
+        let values = match incoming_poise_damage_multiplier {
+            Some(multiplier) => {
+                weapon_poise_damage.get_poise_damage_by_attack_w_multiplier(attack, *multiplier)
+            }
+            None => weapon_poise_damage
+                .get_poise_damage_by_attack(attack)
+                .cloned(),
+        };
+
+        let Some(values) = values.filter(|values| !values.is_empty()) else {
+            continue;
+        };
+
+        let weapon_points = weapon_points_by_class
+            .entry(weapon_poise_damage.class.clone())
+            .or_default();
+
+        weapon_points.push(WeaponPoint {
+            weapon: weapon.clone(),
+            weapon_class: weapon_poise_damage.class.clone(),
+            attack: attack.clone(),
+            x: weapon_points.len() as f64,
+            hits: values.0.iter().map(|value| *value as f64).collect(),
+            total: values.total() as f64,
+        });
+    }
+
+    weapon_points_by_class
+}
+
+/// Every weapon with `attack`, most poise damage first
+pub fn top_weapons(
+    poise_data: &BTreeMap<String, WeaponPoiseDamage>,
+    attack: &Attacks,
+    incoming_poise_damage_multiplier: &Option<f64>,
+) -> Vec<WeaponPoint> {
+    let mut weapon_points =
+        weapon_points_for_attack(poise_data, attack, incoming_poise_damage_multiplier)
+            .into_values()
+            .flatten()
+            .collect::<Vec<_>>();
+
+    weapon_points.sort_by(|a, b| {
+        b.total
+            .total_cmp(&a.total)
+            .then_with(|| a.weapon.cmp(&b.weapon))
+    });
+
+    weapon_points
+}
+
+pub fn top_table(
+    attack: &Attacks,
+    equipment_view: &EquipmentView,
+    limit: usize,
+    weapon_class: Option<&str>,
+) -> Result<Table, QueryError> {
+    let weapon_class = weapon_class.map(find_weapon_class).transpose()?;
+
+    let mut table = Table::new(
+        format!("Top {} Weapons for {}", limit, attack),
+        &["Rank", "Weapon", "Class", "Per Hit", "Poise Damage"],
+    )
+    .with_metadata("Attack", attack);
+
+    if let Some(weapon_class) = weapon_class {
+        table = table.with_metadata("Weapon Class", weapon_class);
+    }
+
+    let weapon_points = top_weapons(
+        &POISE_DATA,
+        attack,
+        &equipment_view.incoming_poise_damage_multiplier,
+    )
+    .into_iter()
+    .filter(|weapon_point| match weapon_class {
+        Some(weapon_class) => &weapon_point.weapon_class == weapon_class,
+        None => true,
+    })
+    .take(limit);
+
+    for (i, weapon_point) in weapon_points.enumerate() {
+        table.push_row(vec![
+            (i + 1).to_string(),
+            weapon_point.weapon,
+            weapon_point.weapon_class,
+            weapon_point
+                .hits
+                .iter()
+                .map(|hit| hit.to_string())
+                .collect::<Vec<_>>()
+                .join(" + "),
+            weapon_point.total.to_string(),
+        ]);
+    }
+
+    Ok(table.with_loadout(equipment_view))
+}
+
+/// Weapon count and innate weapon poise of every class
+pub fn classes_table(
+    weapon_classes: &BTreeMap<String, Vec<String>>,
+    innate_weapon_poise: &BTreeMap<String, u16>,
+) -> Table {
+    let mut table = Table::new(
+        "Weapon Classes",
+        &["Class", "Weapons", "Innate Weapon Poise"],
+    );
+
+    for (weapon_class, weapons) in weapon_classes {
+        let poise = weapons
+            .iter()
+            .filter_map(|weapon| innate_weapon_poise.get(weapon))
+            .collect::<Vec<_>>();
+
+        let poise = match (poise.iter().min(), poise.iter().max()) {
+            (Some(min), Some(max)) if min == max => min.to_string(),
+            (Some(min), Some(max)) => format!("{}-{}", min, max),
+            _ => "-".to_string(),
+        };
+
+        table.push_row(vec![weapon_class.clone(), weapons.len().to_string(), poise]);
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poise_data() -> BTreeMap<String, WeaponPoiseDamage> {
+        let weapon = |name: &str, class: &str, r1: Vec<u16>| {
+            (
+                name.to_string(),
+                WeaponPoiseDamage::test_weapon(name, class)
+                    .with_hits(|w| &mut w.one_handed.r1.chain.one, r1),
+            )
+        };
+
+        BTreeMap::from([
+            weapon("Claymore", "Greatsword", vec![60]),
+            weapon("Greatsword", "Colossal Sword", vec![90]),
+            weapon("Dagger", "Dagger", vec![20, 20]),
+            weapon("Great Stars", "Great Hammer", vec![]),
+        ])
+    }
+
+    #[test]
+    fn test_find_weapon() {
+        let poise_data = poise_data();
+
+        assert_eq!(
+            find_weapon(&poise_data, "Greatsword").unwrap(),
+            "Greatsword"
+        );
+        assert_eq!(find_weapon(&poise_data, "claymore").unwrap(), "Claymore");
+        assert_eq!(find_weapon(&poise_data, "dag").unwrap(), "Dagger");
+        assert_eq!(
+            find_weapon(&poise_data, "great"),
+            Err(QueryError::AmbiguousWeapon(
+                "great".to_string(),
+                vec!["Great Stars".to_string(), "Greatsword".to_string()]
+            ))
+        );
+        assert_eq!(
+            find_weapon(&poise_data, "Zweihander"),
+            Err(QueryError::UnknownWeapon("Zweihander".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_top_weapons() {
+        let top_weapons = top_weapons(&poise_data(), &Attacks::OneHandedR1Chain(0), &Some(0.5));
+
+        assert_eq!(
+            top_weapons
+                .iter()
+                .map(|weapon_point| (weapon_point.weapon.as_str(), weapon_point.total))
+                .collect::<Vec<_>>(),
+            vec![("Greatsword", 45.0), ("Claymore", 30.0), ("Dagger", 20.0)]
+        );
+    }
+
+    #[test]
+    fn test_classes_table() {
+        let weapon_classes = BTreeMap::from([
+            (
+                "Hammer".to_string(),
+                vec!["Mace".to_string(), "Club".to_string()],
+            ),
+            ("Whip".to_string(), vec!["Whip".to_string()]),
+        ]);
+        let innate_weapon_poise = BTreeMap::from([
+            ("Mace".to_string(), 52),
+            ("Club".to_string(), 40),
+            ("Whip".to_string(), 14),
+        ]);
+
+        let table = classes_table(&weapon_classes, &innate_weapon_poise);
+
+        assert_eq!(table.rows[0], vec!["Hammer", "2", "40-52"]);
+        assert_eq!(table.rows[1], vec!["Whip", "1", "14"]);
+    }
+
+    #[test]
+    fn test_staggers_me() {
+        let dagger = WeaponPoiseDamage::test_weapon("Dagger", "Dagger")
+            .with_hits(|w| &mut w.one_handed.r1.chain.one, vec![40]);
+
+        let claymore = WeaponPoiseDamage::test_weapon("Claymore", "Greatsword")
+            .with_hits(|w| &mut w.two_handed.r1.chain.one, vec![100])
+            .with_hits(|w| &mut w.two_handed.r2.chain.one, vec![60, 60]);
+
+        let report = staggers_me([&dagger, &claymore], 0.5, 50.0, 1);
+
+        assert_eq!(report.len(), 2);
+        assert!(report.iter().all(|row| row.weapon == "Claymore"));
+        assert_eq!(report[0].margin, 0.0);
+
+        let report = staggers_me([&dagger, &claymore], 0.5, 50.0, 3);
+
+        assert_eq!(report.len(), 3);
+        assert_eq!(report[0].hits_to_stagger, 3);
+
+        let twinblade = WeaponPoiseDamage::test_weapon("Twinblade", "Twinblade")
+            .with_hits(|w| &mut w.one_handed.r1.chain.one, vec![30, 30]);
+
+        // 19.5 is truncated to 19 per hit
+        assert!(staggers_me([&twinblade], 0.65, 39.0, 1).is_empty());
+        assert_eq!(
+            staggers_me([&twinblade], 0.65, 38.0, 1)[0].poise_damage,
+            38.0
+        );
+    }
+
+    #[test]
+    fn test_weapon_points_for_attack() {
+        let mut poise_data = poise_data();
+        poise_data.insert("Zweihander".to_string(), poise_data["Greatsword"].clone());
+
+        let weapon_points =
+            weapon_points_for_attack(&poise_data, &Attacks::OneHandedR1Chain(0), &Some(0.5));
+
+        assert!(!weapon_points.contains_key("Great Hammer"));
+        assert_eq!(weapon_points["Dagger"][0].hits, vec![10.0, 10.0]);
+        assert_eq!(weapon_points["Dagger"][0].total, 20.0);
+
+        let colossal_swords = &weapon_points["Colossal Sword"];
+
+        assert_eq!(colossal_swords.len(), 2);
+        assert_eq!(colossal_swords[1].weapon, "Zweihander");
+        assert_eq!(colossal_swords[1].x, 1.0);
+        assert_eq!(colossal_swords[1].total, 45.0);
+    }
+}
@@ -0,0 +1,51 @@
+use crate::data::Attacks;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectionEvent {
+    WeaponSelected(String),
+    /// Plotted as the incoming attack, views showing the attacks of the player's own weapon ignore it
+    AttackSelected(Attacks),
+    LoadoutSelected(usize),
+}
+
+/// Views publish selections while they are shown, `App` hands them to every view at the end of the frame
+#[derive(Debug, Default)]
+pub struct SelectionBus {
+    events: Vec<SelectionEvent>,
+}
+
+impl SelectionBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn publish(&mut self, event: SelectionEvent) {
+        log::debug!("Selection event: {:?}", event);
+
+        self.events.push(event);
+    }
+
+    pub fn take_events(&mut self) -> Vec<SelectionEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish() {
+        let mut bus = SelectionBus::new();
+
+        bus.publish(SelectionEvent::WeaponSelected("Claymore".to_string()));
+        bus.publish(SelectionEvent::AttackSelected(Attacks::TwoHandedR1Chain(0)));
+        bus.publish(SelectionEvent::WeaponSelected("Zweihander".to_string()));
+
+        assert_eq!(
+            bus.take_events().last(),
+            Some(&SelectionEvent::WeaponSelected("Zweihander".to_string()))
+        );
+        assert!(bus.take_events().is_empty());
+    }
+}
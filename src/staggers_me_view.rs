@@ -0,0 +1,179 @@
+use egui::{Layout, Slider};
+use egui_extras::{Column, TableBuilder};
+
+use crate::{
+    equipment_view::{effective_poise, EquipmentView},
+    query::{staggers_me, staggers_me_table, StaggeringAttack},
+    static_data::{POISE_DATA, WEAPON_CLASSES},
+    table_export::TableExportControls,
+};
+
+pub struct StaggersMeView {
+    pub is_open: bool,
+    selected_weapon_class: Option<String>,
+    max_hits: u32,
+    is_sorted_descending: bool,
+    report: Vec<StaggeringAttack>,
+    report_inputs: Option<(f64, f64, u32)>,
+    table_export_controls: TableExportControls,
+}
+
+impl Default for StaggersMeView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StaggersMeView {
+    pub fn new() -> Self {
+        Self {
+            is_open: false,
+            selected_weapon_class: None,
+            max_hits: 1,
+            is_sorted_descending: true,
+            report: Vec::new(),
+            report_inputs: None,
+            table_export_controls: TableExportControls::default(),
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, equipment_view: &EquipmentView) {
+        let EquipmentView {
+            incoming_poise_damage_multiplier,
+            hyperarmor,
+            armor_poise,
+            ..
+        } = equipment_view;
+
+        egui::Window::new("What Staggers Me")
+            .id("What Staggers Me Window".into())
+            .resizable(true)
+            .title_bar(true)
+            .open(&mut self.is_open)
+            .show(ui.ctx(), |ui| {
+                let multiplier = incoming_poise_damage_multiplier.unwrap_or(1.0);
+                let poise = effective_poise(hyperarmor.unwrap_or_default(), *armor_poise);
+
+                if self.report_inputs != Some((multiplier, poise, self.max_hits)) {
+                    self.report =
+                        staggers_me(POISE_DATA.values(), multiplier, poise, self.max_hits);
+                    self.report_inputs = Some((multiplier, poise, self.max_hits));
+                }
+
+                ui.label(format!(
+                    "Poise: {:.1}, Incoming Poise Damage Multiplier: {}",
+                    poise, multiplier
+                ));
+
+                ui.add(Slider::new(&mut self.max_hits, 1..=5).text("Max Hits"));
+
+                ui.horizontal_wrapped(|ui| {
+                    ui.selectable_value(&mut self.selected_weapon_class, None, "All");
+
+                    for weapon_class in WEAPON_CLASSES.keys() {
+                        ui.selectable_value(
+                            &mut self.selected_weapon_class,
+                            Some(weapon_class.clone()),
+                            weapon_class,
+                        );
+                    }
+                });
+
+                ui.separator();
+
+                let mut rows = self
+                    .report
+                    .iter()
+                    .filter(|row| match &self.selected_weapon_class {
+                        Some(weapon_class) => &row.class == weapon_class,
+                        None => true,
+                    })
+                    .collect::<Vec<_>>();
+
+                rows.sort_by(|a, b| match self.is_sorted_descending {
+                    true => b.margin.total_cmp(&a.margin),
+                    false => a.margin.total_cmp(&b.margin),
+                });
+
+                ui.label(format!("{} attacks stagger you", rows.len()));
+
+                if let Some(export) = self.table_export_controls.show(ui) {
+                    let mut table = staggers_me_table(rows.iter().copied(), poise)
+                        .with_metadata("Max Hits", self.max_hits)
+                        .with_loadout(equipment_view);
+
+                    if let Some(weapon_class) = &self.selected_weapon_class {
+                        table = table.with_metadata("Weapon Class", weapon_class);
+                    }
+
+                    self.table_export_controls
+                        .export(ui, export, "staggers_me", &table);
+                }
+
+                TableBuilder::new(ui)
+                    .column(Column::auto().resizable(true)) // Weapon
+                    .column(Column::auto().resizable(true)) // Class
+                    .column(Column::auto().resizable(true)) // Attack
+                    .column(Column::auto().resizable(true)) // Poise Damage
+                    .column(Column::auto().resizable(true)) // Margin
+                    .column(Column::auto().resizable(true)) // Hits
+                    .cell_layout(Layout::centered_and_justified(egui::Direction::TopDown))
+                    .header(20.0, |mut header| {
+                        header.col(|ui| {
+                            ui.label("Weapon");
+                        });
+                        header.col(|ui| {
+                            ui.label("Class");
+                        });
+                        header.col(|ui| {
+                            ui.label("Attack");
+                        });
+                        header.col(|ui| {
+                            ui.label("Poise Damage");
+                        });
+                        header.col(|ui| {
+                            let text = match self.is_sorted_descending {
+                                true => "Margin ⏷",
+                                false => "Margin ⏶",
+                            };
+
+                            ui.button(text).clicked().then(|| {
+                                self.is_sorted_descending = !self.is_sorted_descending;
+                            });
+                        });
+                        header.col(|ui| {
+                            ui.label("Hits");
+                        });
+                    })
+                    .body(|body| {
+                        body.rows(20.0, rows.len(), |mut row| {
+                            let staggering_attack = rows[row.index()];
+
+                            row.col(|ui| {
+                                ui.label(&staggering_attack.weapon);
+                            });
+
+                            row.col(|ui| {
+                                ui.label(&staggering_attack.class);
+                            });
+
+                            row.col(|ui| {
+                                ui.label(staggering_attack.attack.to_string());
+                            });
+
+                            row.col(|ui| {
+                                ui.label(format!("{:.1}", staggering_attack.poise_damage));
+                            });
+
+                            row.col(|ui| {
+                                ui.label(format!("{:+.1}", staggering_attack.margin));
+                            });
+
+                            row.col(|ui| {
+                                ui.label(staggering_attack.hits_to_stagger.to_string());
+                            });
+                        });
+                    });
+            });
+    }
+}
@@ -1,12 +1,14 @@
 use std::{
     collections::{BTreeMap, HashSet},
     ops::Deref,
-    sync::LazyLock,
+    sync::{LazyLock, OnceLock},
 };
 
+#[cfg(feature = "download")]
+use crate::download;
 use crate::{
-    data::{load_data, Attacks, WeaponPoiseDamage, POISE_DATA_FILE},
-    download,
+    data::{self, load_data, Attacks, WeaponPoiseDamage, POISE_DATA_FILE},
+    modifiers::{load_modifiers, ModifierRegistry},
 };
 
 pub struct PoiseData(pub BTreeMap<String, WeaponPoiseDamage>);
@@ -20,18 +22,18 @@ impl Deref for PoiseData {
 }
 
 impl PoiseData {
-    pub fn get_poise_damage_values_for_attack(&self, attack: &Attacks) -> Vec<f64> {
+    /// Total poise damage of `attack` for every weapon that has it, truncated per hit like in the plots
+    pub fn get_poise_damage_values_for_attack(
+        &self,
+        attack: &Attacks,
+        multiplier: f64,
+    ) -> Vec<f64> {
         self.iter()
             .filter_map(|(_, poise_data)| {
                 poise_data
-                    .get_poise_damage_by_attack(attack)
-                    .map(|poise_damage_values| {
-                        poise_damage_values
-                            .0
-                            .iter()
-                            .map(|&value| value as f64)
-                            .sum::<f64>()
-                    })
+                    .get_poise_damage_by_attack_w_multiplier(attack, multiplier)
+                    .filter(|poise_damage_values| !poise_damage_values.is_empty())
+                    .map(|poise_damage_values| poise_damage_values.total() as f64)
             })
             .collect()
     }
@@ -76,16 +78,43 @@ impl PoiseData {
     }
 }
 
-pub static POISE_DATA: LazyLock<PoiseData> =
-    LazyLock::new(
-        || match std::path::Path::exists(std::path::Path::new(POISE_DATA_FILE)) {
-            true => PoiseData(load_data()),
-            false => {
-                download::download_poise_data();
-                PoiseData(load_data())
-            }
-        },
-    );
+static LOADED_POISE_DATA: OnceLock<data::PoiseData> = OnceLock::new();
+
+/// Uses `data`, e.g. from `load_data_from`, as `POISE_DATA`, has to be called before first use
+pub fn set_poise_data(data: data::PoiseData) -> Result<(), data::PoiseData> {
+    LOADED_POISE_DATA.set(data)
+}
+
+/// Empty poise data if `POISE_DATA_FILE` can't be read, so the views stay usable
+fn load_data_or_empty() -> data::PoiseData {
+    load_data().unwrap_or_else(|error| {
+        log::error!("Could not load {}: {}", POISE_DATA_FILE, error);
+        Default::default()
+    })
+}
+
+pub static POISE_DATA: LazyLock<PoiseData> = LazyLock::new(|| {
+    if let Some(data) = LOADED_POISE_DATA.get() {
+        return PoiseData(data.clone());
+    }
+
+    match std::path::Path::exists(std::path::Path::new(POISE_DATA_FILE)) {
+        true => PoiseData(load_data_or_empty()),
+        #[cfg(feature = "download")]
+        false => {
+            download::download_poise_data();
+            PoiseData(load_data_or_empty())
+        }
+        #[cfg(not(feature = "download"))]
+        false => {
+            log::warn!(
+                "{} not found, it is only downloaded with the download feature",
+                POISE_DATA_FILE
+            );
+            PoiseData(Default::default())
+        }
+    }
+});
 
 pub static WEAPONS: LazyLock<Vec<String>> = LazyLock::new(|| POISE_DATA.keys().cloned().collect());
 
@@ -108,89 +137,153 @@ pub static WEAPON_CLASSES: LazyLock<BTreeMap<String, Vec<String>>> = LazyLock::n
     weapon_classes
 });
 
-pub const BULLGOAT_MULTIPLIER: f64 = 0.25;
+pub static MODIFIERS: LazyLock<ModifierRegistry> = LazyLock::new(load_modifiers);
+
+/// Bumped whenever the poise rules below change, loadout codes from other rulesets are rejected
+pub const RULESET_VERSION: u32 = 1;
+
 pub const RECOVERY_MULTIPLIER: f64 = 0.8;
 
 pub const COLOSSAL_POISE_DAMAGE_MULTIPLIER: f64 = 0.45;
 pub const POISE_DAMAGE_MULTIPLIER: f64 = 0.65;
 
-pub const INNATE_WEAPON_POISE: LazyLock<BTreeMap<String, u16>> = LazyLock::new(|| {
-    // https://www.reddit.com/r/EldenRingPVP/comments/1dl2j8n/elden_ring_shadow_of_the_erdtree_112_hyper_armour/
-
-    let iwp_classes = [
-        ("Colossal Weapon", 99),
-        ("Colossal Sword", 90),
-        ("Great Hammer", 77),
-        ("Longhaft Axe", 70),
-        ("Greatsword", 59),
-        ("Curved Greatsword", 59),
-        ("Greataxe", 59),
-        ("Great Spear", 59),
-        ("Heavy Thrusting Sword", 59),
-        ("Hammer", 52),
-        ("Flail", 52),
-        ("Halberd", 52),
-        ("Straight Sword", 15),
-        ("Curved Sword", 15),
-        ("Katana", 15),
-        ("Twinblade", 15),
-        ("Axe", 15),
-        ("Spear", 15),
-        ("Fist", 15),
-        ("Reaper", 15),
-        ("Thrusting Sword", 14),
-        ("Whip", 14),
-        ("Dagger", 11),
-        ("Claw", 11),
-        ("Rakshasa's Great Katana", 77),
-        ("Great Katana", 52),
-        ("Light Greatsword", 30),
-        ("Thrusting Shield", 27),
-        ("Bloodfiend's Sacred Spear", 15),
-        ("Backhand Blade", 15),
-        ("Hand-to-Hand", 15),
-        ("Beast Claw", 14),
-        ("Perfume Bottle", 14),
-        ("Throwing Blade", 11),
-    ]
-    .into_iter()
-    .map(|(weapon, poise)| (weapon.to_string(), poise as u16))
-    .collect::<BTreeMap<_, _>>();
+pub static INNATE_WEAPON_POISE: LazyLock<BTreeMap<String, u16>> =
+    LazyLock::new(|| innate_weapon_poise(&POISE_DATA));
+
+// https://www.reddit.com/r/EldenRingPVP/comments/1dl2j8n/elden_ring_shadow_of_the_erdtree_112_hyper_armour/
+const IWP_CLASSES: [(&str, u16); 34] = [
+    ("Colossal Weapon", 99),
+    ("Colossal Sword", 90),
+    ("Great Hammer", 77),
+    ("Longhaft Axe", 70),
+    ("Greatsword", 59),
+    ("Curved Greatsword", 59),
+    ("Greataxe", 59),
+    ("Great Spear", 59),
+    ("Heavy Thrusting Sword", 59),
+    ("Hammer", 52),
+    ("Flail", 52),
+    ("Halberd", 52),
+    ("Straight Sword", 15),
+    ("Curved Sword", 15),
+    ("Katana", 15),
+    ("Twinblade", 15),
+    ("Axe", 15),
+    ("Spear", 15),
+    ("Fist", 15),
+    ("Reaper", 15),
+    ("Thrusting Sword", 14),
+    ("Whip", 14),
+    ("Dagger", 11),
+    ("Claw", 11),
+    ("Rakshasa's Great Katana", 77),
+    ("Great Katana", 52),
+    ("Light Greatsword", 30),
+    ("Thrusting Shield", 27),
+    ("Bloodfiend's Sacred Spear", 15),
+    ("Backhand Blade", 15),
+    ("Hand-to-Hand", 15),
+    ("Beast Claw", 14),
+    ("Perfume Bottle", 14),
+    ("Throwing Blade", 11),
+];
 
+/// The weapon's own entry first, its class' second
+fn iwp_entry(weapon: &str, weapon_class: &str) -> Option<(&'static str, u16)> {
+    IWP_CLASSES
+        .iter()
+        .find(|(name, _)| *name == weapon)
+        .or_else(|| IWP_CLASSES.iter().find(|(name, _)| *name == weapon_class))
+        .copied()
+}
+
+/// Innate weapon poise of a single weapon, 0 if neither it nor its class have one
+pub fn weapon_innate_poise(weapon: &str, weapon_class: &str) -> u16 {
+    iwp_entry(weapon, weapon_class).map_or(0, |(_, poise)| poise)
+}
+
+/// Innate weapon poise of every weapon in `poise_data`
+fn innate_weapon_poise(poise_data: &BTreeMap<String, WeaponPoiseDamage>) -> BTreeMap<String, u16> {
     let mut iwp_data = BTreeMap::<String, u16>::new();
 
     let mut iwp_classes_used = HashSet::new();
 
-    for weapon in WEAPONS.iter() {
-        let weapon_class = &POISE_DATA.get::<String>(weapon).unwrap().class;
-
-        if iwp_classes.contains_key(weapon) {
-            iwp_data.insert(weapon.clone(), iwp_classes[weapon]);
-            iwp_classes_used.insert(weapon.clone());
-        } else if iwp_classes.contains_key(weapon_class) {
-            iwp_data.insert(weapon.clone(), iwp_classes[weapon_class]);
-            iwp_classes_used.insert(weapon_class.clone());
-        } else {
-            iwp_data.insert(weapon.clone(), 0);
+    for (weapon, weapon_poise_damage) in poise_data {
+        match iwp_entry(weapon, &weapon_poise_damage.class) {
+            Some((name, poise)) => {
+                iwp_data.insert(weapon.clone(), poise);
+                iwp_classes_used.insert(name);
+            }
+            None => {
+                iwp_data.insert(weapon.clone(), 0);
+            }
         }
     }
 
-    if iwp_classes.len() != iwp_classes_used.len() {
-        let mut iwp_classes_not_used = iwp_classes
-            .keys()
-            .cloned()
-            .collect::<HashSet<String>>()
-            .difference(&iwp_classes_used)
-            .cloned()
-            .collect::<Vec<String>>();
+    // Classes can be missing from a smaller `--data` file or from empty poise data
+    let mut iwp_classes_not_used = IWP_CLASSES
+        .iter()
+        .map(|(name, _)| *name)
+        .filter(|name| !iwp_classes_used.contains(name))
+        .collect::<Vec<_>>();
 
+    if !iwp_classes_not_used.is_empty() {
         iwp_classes_not_used.sort();
 
-        panic!(
+        log::warn!(
             "The following weapon classes are not used: {:?}",
             iwp_classes_not_used
         );
     }
 
     iwp_data
-});
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_innate_weapon_poise() {
+        assert!(innate_weapon_poise(&BTreeMap::new()).is_empty());
+
+        let weapon = |name: &str, class: &str| {
+            (
+                name.to_string(),
+                WeaponPoiseDamage::test_weapon(name, class),
+            )
+        };
+
+        let poise_data = BTreeMap::from([
+            weapon("Claymore", "Greatsword"),
+            weapon("Rakshasa's Great Katana", "Great Katana"),
+            weapon("Torch", "Torch"),
+        ]);
+
+        assert_eq!(
+            innate_weapon_poise(&poise_data),
+            BTreeMap::from([
+                ("Claymore".to_string(), 59),
+                ("Rakshasa's Great Katana".to_string(), 77),
+                ("Torch".to_string(), 0),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_get_poise_damage_values_for_attack() {
+        let twinblade = WeaponPoiseDamage::test_weapon("Twinblade", "Twinblade")
+            .with_hits(|w| &mut w.one_handed.r1.chain.one, vec![30, 30]);
+
+        let poise_data = PoiseData(BTreeMap::from([("Twinblade".to_string(), twinblade)]));
+
+        // 19.5 is truncated to 19 per hit
+        assert_eq!(
+            poise_data.get_poise_damage_values_for_attack(&Attacks::OneHandedR1Chain(0), 0.65),
+            vec![38.0]
+        );
+        assert!(poise_data
+            .get_poise_damage_values_for_attack(&Attacks::Shieldpoke, 0.65)
+            .is_empty());
+    }
+}
@@ -0,0 +1,390 @@
+use std::path::Path;
+
+use serde_json::{json, Map, Value};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+#[cfg(feature = "gui")]
+use crate::export_folder::ExportFolder;
+use crate::{equipment_view::EquipmentView, loadout_code::LoadoutCode};
+
+/// Cells as shown on screen, with the settings they were computed with
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Table {
+    pub title: String,
+    pub metadata: Vec<(String, String)>,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(title: impl Into<String>, columns: &[&str]) -> Self {
+        Self {
+            title: title.into(),
+            metadata: Vec::new(),
+            columns: columns.iter().map(|column| column.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn with_metadata(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.metadata.push((name.into(), value.to_string()));
+        self
+    }
+
+    /// Records the multiplier, poise and loadout code of `equipment_view`
+    pub fn with_loadout(mut self, equipment_view: &EquipmentView) -> Self {
+        self.metadata.extend(loadout_metadata(equipment_view));
+        self
+    }
+
+    pub fn push_row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    /// Metadata lines start with `#`, e.g. for `csv::ReaderBuilder::comment`
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+
+        csv.push_str(&format!("# {}\n", self.title));
+
+        for (name, value) in &self.metadata {
+            csv.push_str(&format!("# {}: {}\n", name, value));
+        }
+
+        let mut writer = csv::WriterBuilder::new()
+            .flexible(true)
+            .from_writer(Vec::new());
+
+        for record in std::iter::once(&self.columns).chain(&self.rows) {
+            if let Err(error) = writer.write_record(record) {
+                log::warn!("Failed to write CSV record: {}", error);
+            }
+        }
+
+        match writer.into_inner() {
+            Ok(bytes) => csv.push_str(&String::from_utf8_lossy(&bytes)),
+            Err(error) => log::warn!("Failed to write CSV: {}", error),
+        }
+
+        csv
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let escape = |text: &str| text.replace('|', "\\|");
+
+        let row = |cells: &[String]| {
+            format!(
+                "| {} |\n",
+                cells
+                    .iter()
+                    .map(|cell| escape(cell))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            )
+        };
+
+        let mut markdown = format!("## {}\n\n", self.title);
+
+        for (name, value) in &self.metadata {
+            markdown.push_str(&format!("- **{}:** {}\n", name, value));
+        }
+
+        if !self.metadata.is_empty() {
+            markdown.push('\n');
+        }
+
+        markdown.push_str(&row(&self.columns));
+        markdown.push_str(&row(&vec!["---".to_string(); self.columns.len()]));
+
+        for cells in &self.rows {
+            markdown.push_str(&row(cells));
+        }
+
+        markdown
+    }
+
+    /// Columns padded to their widest cell, for terminals
+    pub fn to_text(&self) -> String {
+        let width = |text: &str| text.chars().count();
+
+        let widths = (0..self.columns.len())
+            .map(|column| {
+                std::iter::once(&self.columns)
+                    .chain(&self.rows)
+                    .filter_map(|cells| cells.get(column))
+                    .map(|cell| width(cell))
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        let row = |cells: &[String]| {
+            let line = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, column_width)| {
+                    format!("{}{}", cell, " ".repeat(column_width - width(cell)))
+                })
+                .collect::<Vec<_>>()
+                .join("  ");
+
+            format!("{}\n", line.trim_end())
+        };
+
+        let mut text = format!("{}\n", self.title);
+
+        for (name, value) in &self.metadata {
+            text.push_str(&format!("{}: {}\n", name, value));
+        }
+
+        text.push('\n');
+        text.push_str(&row(&self.columns));
+        text.push_str(&row(&widths
+            .iter()
+            .map(|column_width| "-".repeat(*column_width))
+            .collect::<Vec<_>>()));
+
+        for cells in &self.rows {
+            text.push_str(&row(cells));
+        }
+
+        text
+    }
+
+    /// Rows become objects keyed by column
+    pub fn to_json_value(&self) -> Value {
+        let metadata = self
+            .metadata
+            .iter()
+            .map(|(name, value)| (name.clone(), Value::String(value.clone())))
+            .collect::<Map<_, _>>();
+
+        let rows = self
+            .rows
+            .iter()
+            .map(|cells| {
+                Value::Object(
+                    self.columns
+                        .iter()
+                        .cloned()
+                        .zip(cells.iter().cloned().map(Value::String))
+                        .collect(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "title": self.title,
+            "metadata": metadata,
+            "columns": self.columns,
+            "rows": rows,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_json_value()).unwrap_or_default()
+    }
+
+    pub fn to_format(&self, format: TableFormat) -> String {
+        match format {
+            TableFormat::Csv => self.to_csv(),
+            TableFormat::Markdown => self.to_markdown(),
+            TableFormat::Json => self.to_json(),
+        }
+    }
+}
+
+pub fn loadout_metadata(equipment_view: &EquipmentView) -> Vec<(String, String)> {
+    let optional = |value: Option<f64>| {
+        value
+            .map(|value| format!("{:.3}", value))
+            .unwrap_or("-".to_string())
+    };
+
+    vec![
+        (
+            "Incoming Poise Damage Multiplier".to_string(),
+            optional(equipment_view.incoming_poise_damage_multiplier),
+        ),
+        (
+            "Hyperarmor".to_string(),
+            optional(equipment_view.hyperarmor),
+        ),
+        (
+            "Armor Poise".to_string(),
+            equipment_view.armor_poise.to_string(),
+        ),
+        (
+            "Loadout Code".to_string(),
+            LoadoutCode::from_state(&equipment_view.state()).encode(),
+        ),
+    ]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumIter, Display)]
+pub enum TableFormat {
+    #[strum(to_string = "CSV")]
+    Csv,
+    #[strum(to_string = "Markdown")]
+    Markdown,
+    #[strum(to_string = "JSON")]
+    Json,
+}
+
+impl TableFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TableFormat::Csv => "csv",
+            TableFormat::Markdown => "md",
+            TableFormat::Json => "json",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+
+        TableFormat::iter().find(|format| format.extension() == extension)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableExport {
+    Copy(TableFormat),
+    Save(TableFormat),
+}
+
+/// Copy and save menus of a table view and the outcome of the last export
+#[cfg(feature = "gui")]
+#[derive(Default)]
+pub struct TableExportControls {
+    folder: ExportFolder,
+}
+
+#[cfg(feature = "gui")]
+impl TableExportControls {
+    /// Returns the clicked menu entry, if any
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<TableExport> {
+        let mut clicked_export = None;
+
+        ui.horizontal_wrapped(|ui| {
+            ui.menu_button("Copy As", |ui| {
+                for format in TableFormat::iter() {
+                    ui.button(format.to_string()).clicked().then(|| {
+                        clicked_export = Some(TableExport::Copy(format));
+                        ui.close_menu();
+                    });
+                }
+            });
+
+            ui.menu_button("Save As", |ui| {
+                ui.horizontal(|ui| self.folder.show_folder(ui));
+
+                for format in TableFormat::iter() {
+                    ui.button(format.to_string()).clicked().then(|| {
+                        clicked_export = Some(TableExport::Save(format));
+                        ui.close_menu();
+                    });
+                }
+            });
+
+            self.folder.show_last_export(ui);
+        });
+
+        clicked_export
+    }
+
+    /// Copies to the clipboard or saves to a new `<file stem>.<extension>` in the export folder
+    pub fn export(&mut self, ui: &egui::Ui, export: TableExport, file_stem: &str, table: &Table) {
+        match export {
+            TableExport::Copy(format) => {
+                let text = table.to_format(format);
+                ui.output_mut(|output| output.copied_text = text);
+
+                self.folder.set_last_export(format!(
+                    "Copied {} rows as {}",
+                    table.rows.len(),
+                    format
+                ));
+            }
+            TableExport::Save(format) => {
+                let path = self.folder.new_path(file_stem, format.extension());
+
+                match std::fs::write(&path, table.to_format(format)) {
+                    Ok(()) => self
+                        .folder
+                        .set_last_export(format!("Saved {}", path.display())),
+                    Err(error) => {
+                        log::warn!("Failed to save {}: {}", path.display(), error);
+                        self.folder
+                            .set_last_export(format!("Save failed: {}", error));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        let mut table = Table::new("What Staggers Me", &["Weapon", "Attack", "Margin"])
+            .with_metadata("Incoming Poise Damage Multiplier", 0.75);
+
+        table.push_row(vec![
+            "Claymore".to_string(),
+            "Two Handed R1 Chain(0)".to_string(),
+            "+4.5".to_string(),
+        ]);
+        table.push_row(vec![
+            "Hand | Foot".to_string(),
+            "Shieldpoke".to_string(),
+            "-1.0".to_string(),
+        ]);
+
+        table
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let csv = table().to_csv();
+
+        let mut reader = csv::ReaderBuilder::new()
+            .comment(Some(b'#'))
+            .from_reader(csv.as_bytes());
+
+        assert!(csv.starts_with("# What Staggers Me\n# Incoming Poise Damage Multiplier: 0.75\n"));
+        assert_eq!(
+            reader.headers().unwrap(),
+            vec!["Weapon", "Attack", "Margin"]
+        );
+        assert_eq!(reader.records().count(), 2);
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let markdown = table().to_markdown();
+
+        assert!(markdown.contains("- **Incoming Poise Damage Multiplier:** 0.75\n"));
+        assert!(markdown.contains("| Weapon | Attack | Margin |\n| --- | --- | --- |\n"));
+        assert!(markdown.contains("| Hand \\| Foot | Shieldpoke | -1.0 |\n"));
+    }
+
+    #[test]
+    fn test_to_text() {
+        let text = table().to_text();
+
+        assert!(text.contains("\nWeapon       Attack                  Margin\n"));
+        assert!(text.contains("\nHand | Foot  Shieldpoke              -1.0\n"));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let json = serde_json::from_str::<Value>(&table().to_json()).unwrap();
+
+        assert_eq!(json["metadata"]["Incoming Poise Damage Multiplier"], "0.75");
+        assert_eq!(json["rows"][0]["Weapon"], "Claymore");
+        assert_eq!(json["rows"].as_array().unwrap().len(), 2);
+    }
+}
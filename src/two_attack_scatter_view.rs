@@ -0,0 +1,367 @@
+use std::collections::BTreeMap;
+
+use egui::{Color32, Pos2};
+use egui_plot::{Legend, Line, LineStyle, Plot, PlotPoint, PlotPoints, PlotResponse, Points};
+
+use crate::{
+    data::{Attacks, WeaponPoiseDamage},
+    equipment_view::EquipmentView,
+    one_attack_plot_view::{weapon_class_color, HOVER_TOLERANCE},
+    plot_export::{Chart, ChartItem, ExportControls},
+    selection::{SelectionBus, SelectionEvent},
+    static_data::POISE_DATA,
+};
+
+/// A weapon's total poise damage for attack A and attack B
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScatterPoint {
+    pub weapon: String,
+    pub weapon_class: String,
+    pub a: f64,
+    pub b: f64,
+}
+
+/// Weapons without poise damage for either attack are left out
+pub fn scatter_points(
+    poise_data: &BTreeMap<String, WeaponPoiseDamage>,
+    attack_a: &Attacks,
+    attack_b: &Attacks,
+    incoming_poise_damage_multiplier: f64,
+) -> BTreeMap<String, Vec<ScatterPoint>> {
+    let mut scatter_points_by_class = BTreeMap::<String, Vec<ScatterPoint>>::new();
+
+    let total = |weapon_poise_damage: &WeaponPoiseDamage, attack| {
+        weapon_poise_damage
+            .get_poise_damage_by_attack_w_multiplier(attack, incoming_poise_damage_multiplier)
+            .filter(|values| !values.is_empty())
+            .map(|values| values.total() as f64)
+    };
+
+    for (weapon, weapon_poise_damage) in poise_data.iter() {
+        let (Some(a), Some(b)) = (
+            total(weapon_poise_damage, attack_a),
+            total(weapon_poise_damage, attack_b),
+        ) else {
+            continue;
+        };
+
+        scatter_points_by_class
+            .entry(weapon_poise_damage.class.clone())
+            .or_default()
+            .push(ScatterPoint {
+                weapon: weapon.clone(),
+                weapon_class: weapon_poise_damage.class.clone(),
+                a,
+                b,
+            });
+    }
+
+    scatter_points_by_class
+}
+
+/// Largest value on either axis, so that the diagonal and poise squares cover every point
+fn max_value(
+    scatter_points_by_class: &BTreeMap<String, Vec<ScatterPoint>>,
+    hyperarmor: f64,
+    armor_poise: f64,
+) -> f64 {
+    scatter_points_by_class
+        .values()
+        .flatten()
+        .map(|scatter_point| scatter_point.a.max(scatter_point.b))
+        .fold(hyperarmor.max(armor_poise), f64::max)
+}
+
+/// Lines from an axis to the diagonal and on to the other axis, a point inside is tanked by both attacks
+fn poise_squares(hyperarmor: f64, armor_poise: f64) -> Vec<(&'static str, Color32, Vec<[f64; 2]>)> {
+    [
+        ("Armor Poise", Color32::from_rgb(255, 0, 0), armor_poise),
+        ("Hyperarmor", Color32::from_rgb(0, 255, 0), hyperarmor),
+    ]
+    .into_iter()
+    .filter(|(_, _, poise)| *poise > 0.0)
+    .map(|(name, color, poise)| {
+        (
+            name,
+            color,
+            vec![[poise, 0.0], [poise, poise], [0.0, poise]],
+        )
+    })
+    .collect()
+}
+
+pub fn scatter_chart(
+    scatter_points_by_class: &BTreeMap<String, Vec<ScatterPoint>>,
+    attack_a: &Attacks,
+    attack_b: &Attacks,
+    point_radius: f32,
+    hyperarmor: f64,
+    armor_poise: f64,
+) -> Chart {
+    let mut chart = Chart::new(
+        format!("{} vs {}", attack_a, attack_b),
+        format!("{} Poise Damage", attack_a),
+        format!("{} Poise Damage", attack_b),
+    );
+
+    for (weapon_class, scatter_points) in scatter_points_by_class {
+        chart.items.push(ChartItem::Points {
+            name: Some(weapon_class.clone()),
+            color: weapon_class_color(weapon_class),
+            shape: egui_plot::MarkerShape::Circle,
+            radius: point_radius,
+            points: scatter_points
+                .iter()
+                .map(|scatter_point| [scatter_point.a, scatter_point.b])
+                .collect(),
+        });
+    }
+
+    let max_value = max_value(scatter_points_by_class, hyperarmor, armor_poise);
+
+    chart.items.push(ChartItem::Line {
+        name: Some("A = B".to_string()),
+        color: Color32::GRAY,
+        is_dashed: true,
+        points: vec![[0.0, 0.0], [max_value, max_value]],
+    });
+
+    for (name, color, points) in poise_squares(hyperarmor, armor_poise) {
+        chart.items.push(ChartItem::Line {
+            name: Some(name.to_string()),
+            color,
+            is_dashed: false,
+            points,
+        });
+    }
+
+    chart
+}
+
+pub struct TwoAttackScatterView {
+    pub is_open: bool,
+    attack_a: Option<Attacks>,
+    attack_b: Option<Attacks>,
+    point_radius: f32,
+    export_controls: ExportControls,
+}
+
+impl Default for TwoAttackScatterView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TwoAttackScatterView {
+    pub fn new() -> Self {
+        Self {
+            is_open: false,
+            attack_a: Some(Attacks::OneHandedR1Chain(0)),
+            attack_b: Some(Attacks::TwoHandedR2Running),
+            point_radius: 3.0,
+            export_controls: ExportControls::default(),
+        }
+    }
+
+    /// An attack selected elsewhere becomes attack A
+    pub fn on_selection_event(&mut self, event: &SelectionEvent) {
+        if let SelectionEvent::AttackSelected(attack) = event {
+            self.attack_a = Some(attack.clone());
+        }
+    }
+
+    /// Uses the weapon classes selected in the one attack plot
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        equipment_view: &EquipmentView,
+        selected_weapon_classes: &BTreeMap<String, bool>,
+        selection_bus: &mut SelectionBus,
+    ) {
+        const INITIAL_WINDOW_SIZE: [f32; 2] = [500.0, 500.0];
+
+        let multiplier = equipment_view
+            .incoming_poise_damage_multiplier
+            .unwrap_or(1.0);
+        let hyperarmor = equipment_view.hyperarmor.unwrap_or_default();
+        let armor_poise = equipment_view.armor_poise as f64;
+
+        egui::Window::new("Two Attack Scatter")
+            .id("Two Attack Scatter Window".into())
+            .resizable(true)
+            .title_bar(true)
+            .default_size(INITIAL_WINDOW_SIZE)
+            .open(&mut self.is_open)
+            .show(ui.ctx(), |ui| {
+                egui::Grid::new("Two Attack Scatter Attacks").show(ui, |ui| {
+                    ui.label("Attack A (x): ");
+                    ui.push_id("Attack A", |ui| {
+                        if Attacks::combobox(ui, &mut self.attack_a) {
+                            if let Some(attack) = &self.attack_a {
+                                selection_bus
+                                    .publish(SelectionEvent::AttackSelected(attack.clone()));
+                            }
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Attack B (y): ");
+                    ui.push_id("Attack B", |ui| {
+                        Attacks::combobox(ui, &mut self.attack_b);
+                    });
+                    ui.end_row();
+                });
+
+                ui.add(egui::Slider::new(&mut self.point_radius, 0.0..=20.0).text("Point Radius"));
+
+                let (Some(attack_a), Some(attack_b)) = (&self.attack_a, &self.attack_b) else {
+                    return;
+                };
+
+                let scatter_points_by_class =
+                    scatter_points(&POISE_DATA, attack_a, attack_b, multiplier)
+                        .into_iter()
+                        .filter(|(weapon_class, _)| {
+                            selected_weapon_classes
+                                .get(weapon_class)
+                                .copied()
+                                .unwrap_or(true)
+                        })
+                        .collect::<BTreeMap<_, _>>();
+
+                let max_value = max_value(&scatter_points_by_class, hyperarmor, armor_poise);
+
+                let point_radius = self.point_radius;
+
+                if let Some(format) = self.export_controls.show(ui) {
+                    let chart = scatter_chart(
+                        &scatter_points_by_class,
+                        attack_a,
+                        attack_b,
+                        point_radius,
+                        hyperarmor,
+                        armor_poise,
+                    );
+
+                    self.export_controls
+                        .export(format, &[("two_attack_scatter", chart)]);
+                }
+
+                let PlotResponse {
+                    response,
+                    inner: hovered_scatter_point,
+                    ..
+                } = Plot::new("Two Attack Scatter")
+                    .legend(Legend::default())
+                    .data_aspect(1.0)
+                    .show_x(false)
+                    .show_y(false)
+                    .x_axis_label(format!("{} Poise Damage", attack_a))
+                    .y_axis_label(format!("{} Poise Damage", attack_b))
+                    .show(ui, |plot_ui| {
+                        for (weapon_class, scatter_points) in &scatter_points_by_class {
+                            let points = scatter_points
+                                .iter()
+                                .map(|scatter_point| [scatter_point.a, scatter_point.b])
+                                .collect::<PlotPoints>();
+
+                            plot_ui.points(
+                                Points::new(points)
+                                    .name(weapon_class)
+                                    .color(weapon_class_color(weapon_class))
+                                    .radius(point_radius),
+                            );
+                        }
+
+                        // weapons above the diagonal hit harder with attack B
+                        plot_ui.line(
+                            Line::new(vec![[0.0, 0.0], [max_value, max_value]])
+                                .color(Color32::GRAY)
+                                .style(LineStyle::dashed_loose())
+                                .name("A = B"),
+                        );
+
+                        for (name, color, points) in poise_squares(hyperarmor, armor_poise) {
+                            plot_ui.line(Line::new(points).color(color).name(name));
+                        }
+
+                        let pointer = plot_ui
+                            .pointer_coordinate()
+                            .map(|coordinate| plot_ui.screen_from_plot(coordinate))?;
+
+                        let to_screen = |scatter_point: &ScatterPoint| -> Pos2 {
+                            plot_ui
+                                .screen_from_plot(PlotPoint::new(scatter_point.a, scatter_point.b))
+                        };
+
+                        scatter_points_by_class
+                            .values()
+                            .flatten()
+                            .map(|scatter_point| {
+                                (to_screen(scatter_point).distance(pointer), scatter_point)
+                            })
+                            .filter(|(distance, _)| *distance <= point_radius + HOVER_TOLERANCE)
+                            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+                            .map(|(_, scatter_point)| scatter_point.clone())
+                    });
+
+                if let Some(scatter_point) = &hovered_scatter_point {
+                    response.clone().on_hover_ui_at_pointer(|ui| {
+                        ui.strong(&scatter_point.weapon);
+                        ui.label(&scatter_point.weapon_class);
+                        ui.label(format!("{}: {}", attack_a, scatter_point.a));
+                        ui.label(format!("{}: {}", attack_b, scatter_point.b));
+                    });
+
+                    if response.clicked() {
+                        selection_bus
+                            .publish(SelectionEvent::WeaponSelected(scatter_point.weapon.clone()));
+                    }
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scatter_points() {
+        let weapon = |class: &str, r1: Vec<u16>, running_r2: Vec<u16>| {
+            WeaponPoiseDamage::test_weapon("", class)
+                .with_hits(|w| &mut w.one_handed.r1.chain.one, r1)
+                .with_hits(|w| &mut w.two_handed.r2.running, running_r2)
+        };
+
+        let poise_data = BTreeMap::from([
+            (
+                "Claymore".to_string(),
+                weapon("Greatsword", vec![60], vec![120]),
+            ),
+            ("Dagger".to_string(), weapon("Dagger", vec![20, 20], vec![])),
+            (
+                "Zweihander".to_string(),
+                weapon("Colossal Sword", vec![80], vec![140]),
+            ),
+        ]);
+
+        let scatter_points = scatter_points(
+            &poise_data,
+            &Attacks::OneHandedR1Chain(0),
+            &Attacks::TwoHandedR2Running,
+            0.5,
+        );
+
+        assert!(!scatter_points.contains_key("Dagger"));
+        assert_eq!(
+            scatter_points["Greatsword"],
+            vec![ScatterPoint {
+                weapon: "Claymore".to_string(),
+                weapon_class: "Greatsword".to_string(),
+                a: 30.0,
+                b: 60.0,
+            }]
+        );
+    }
+}
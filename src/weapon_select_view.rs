@@ -1,6 +1,81 @@
-use egui::Layout;
+use std::collections::BTreeSet;
 
-use crate::static_data::{WEAPONS, WEAPON_CLASSES};
+use egui::{Key, Layout};
+use serde::{Deserialize, Serialize};
+
+use crate::static_data::{POISE_DATA, WEAPONS, WEAPON_CLASSES};
+
+const MAX_RECENTS: usize = 10;
+
+/// Favourite and recently selected weapons, shared by every weapon select view
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeaponSelectHistory {
+    pub favourites: BTreeSet<String>,
+    pub recents: Vec<String>,
+}
+
+impl WeaponSelectHistory {
+    fn id() -> egui::Id {
+        egui::Id::new("weapon_select_history")
+    }
+
+    pub fn push_recent(&mut self, weapon: &str) {
+        self.recents.retain(|recent| recent != weapon);
+        self.recents.insert(0, weapon.to_string());
+        self.recents.truncate(MAX_RECENTS);
+    }
+
+    pub fn toggle_favourite(&mut self, weapon: &str) {
+        if !self.favourites.remove(weapon) {
+            self.favourites.insert(weapon.to_string());
+        }
+    }
+
+    /// Drops weapons no longer in the poise data, e.g. renamed after a new download
+    pub fn retain_known(&mut self, is_known: impl Fn(&str) -> bool) {
+        self.favourites.retain(|weapon| is_known(weapon));
+        self.recents.retain(|weapon| is_known(weapon));
+    }
+}
+
+// Example
+// "rak gk" matches "Rakshasa's Great Katana"
+// every whitespace separated part of the query has to appear in order in the candidate,
+// matches at word starts and consecutive matches score higher
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let candidate = candidate.to_lowercase().chars().collect::<Vec<char>>();
+
+    let mut score = 0;
+
+    for part in query.to_lowercase().split_whitespace() {
+        let mut position = 0;
+        let mut previous_match = None;
+        let mut part_score = None;
+
+        for query_char in part.chars() {
+            let index = (position..candidate.len()).find(|i| candidate[*i] == query_char)?;
+
+            let is_word_start = index == 0 || !candidate[index - 1].is_alphanumeric();
+            let is_consecutive = previous_match.is_some_and(|previous| previous + 1 == index);
+
+            part_score = Some(
+                part_score.unwrap_or(0)
+                    + match (is_word_start, is_consecutive) {
+                        (true, _) => 3,
+                        (false, true) => 2,
+                        (false, false) => 1,
+                    },
+            );
+
+            previous_match = Some(index);
+            position = index + 1;
+        }
+
+        score += part_score.unwrap_or(0);
+    }
+
+    Some(score)
+}
 
 #[derive(Default)]
 pub struct WeaponSelectView {
@@ -8,6 +83,9 @@ pub struct WeaponSelectView {
     pub selected_weapon: Option<String>,
     pub selected_weapon_class: Option<String>,
     id: String,
+    query: String,
+    highlighted: usize,
+    was_open: bool,
 }
 
 impl WeaponSelectView {
@@ -19,15 +97,84 @@ impl WeaponSelectView {
             selected_weapon: None,
             selected_weapon_class: None,
             id: format!("weapon_select_view_{}", random_id),
+            query: String::new(),
+            highlighted: 0,
+            was_open: false,
         }
     }
 
+    fn filtered_weapons(selected_weapon_class: &Option<String>, query: &str) -> Vec<String> {
+        let weapons = match selected_weapon_class {
+            Some(selected_weapon_class) => WEAPON_CLASSES.get(selected_weapon_class).unwrap(),
+            None => &*WEAPONS,
+        };
+
+        if query.trim().is_empty() {
+            return weapons.clone();
+        }
+
+        let mut scored_weapons = weapons
+            .iter()
+            .filter_map(|weapon| Some((fuzzy_score(query, weapon)?, weapon)))
+            .collect::<Vec<_>>();
+
+        scored_weapons.sort_by(|(a_score, a), (b_score, b)| b_score.cmp(a_score).then(a.cmp(b)));
+
+        scored_weapons
+            .into_iter()
+            .map(|(_, weapon)| weapon.clone())
+            .collect()
+    }
+
+    fn weapon_row(
+        ui: &mut egui::Ui,
+        weapon: &str,
+        is_highlighted: bool,
+        history: &mut WeaponSelectHistory,
+    ) -> egui::Response {
+        ui.horizontal(|ui| {
+            let star = match history.favourites.contains(weapon) {
+                true => "★",
+                false => "☆",
+            };
+
+            ui.small_button(star).clicked().then(|| {
+                history.toggle_favourite(weapon);
+            });
+
+            ui.selectable_label(is_highlighted, weapon)
+        })
+        .inner
+    }
+
     pub fn show(&mut self, ui: &mut egui::Ui, opened_context: &str) {
+        let just_opened = self.is_open && !self.was_open;
+        self.was_open = self.is_open;
+
+        let mut history = ui.ctx().data_mut(|data| {
+            data.get_persisted_mut_or_default::<WeaponSelectHistory>(WeaponSelectHistory::id())
+                .clone()
+        });
+        let previous_history = history.clone();
+
+        history.retain_known(|weapon| POISE_DATA.contains_key(weapon));
+
         egui::Window::new(format!("{} Weapon Select", opened_context))
             .title_bar(true)
             .open(&mut self.is_open)
             .id(self.id.clone().into())
             .show(ui.ctx(), |ui| {
+                let search =
+                    ui.add(egui::TextEdit::singleline(&mut self.query).hint_text("Search weapons"));
+
+                if just_opened {
+                    search.request_focus();
+                }
+
+                if search.changed() {
+                    self.highlighted = 0;
+                }
+
                 ui.horizontal_wrapped(|ui| {
                     ui.selectable_value(&mut self.selected_weapon_class, None, "All");
 
@@ -42,29 +189,131 @@ impl WeaponSelectView {
 
                 ui.separator();
 
+                let weapons = Self::filtered_weapons(&self.selected_weapon_class, &self.query);
+
+                // Only keys typed into the search, other fields and selectors keep theirs
+                ui.input(|i| {
+                    if search.has_focus() && i.key_pressed(Key::ArrowDown) {
+                        self.highlighted += 1;
+                    }
+
+                    if search.has_focus() && i.key_pressed(Key::ArrowUp) {
+                        self.highlighted = self.highlighted.saturating_sub(1);
+                    }
+
+                    self.highlighted = self.highlighted.min(weapons.len().saturating_sub(1));
+
+                    // Enter takes the focus from a single line TextEdit
+                    if search.lost_focus() && i.key_pressed(Key::Enter) {
+                        self.selected_weapon = weapons.get(self.highlighted).cloned();
+                    }
+                });
+
+                if self.query.trim().is_empty() {
+                    let sections: [(&str, Vec<String>); 2] = [
+                        ("Favourites", history.favourites.iter().cloned().collect()),
+                        ("Recent", history.recents.clone()),
+                    ];
+
+                    for (title, weapons) in sections {
+                        if weapons.is_empty() {
+                            continue;
+                        }
+
+                        ui.collapsing(title, |ui| {
+                            for weapon in weapons {
+                                if Self::weapon_row(ui, &weapon, false, &mut history).clicked() {
+                                    self.selected_weapon = Some(weapon.clone());
+                                }
+                            }
+                        });
+                    }
+
+                    ui.separator();
+                }
+
                 ui.with_layout(Layout::left_to_right(egui::Align::Min), |ui| {
                     ui.vertical(|ui| {
                         egui::ScrollArea::vertical().show(ui, |ui| {
-                            if let Some(selected_weapon_class) = &self.selected_weapon_class {
-                                for weapon in WEAPON_CLASSES.get(selected_weapon_class).unwrap() {
-                                    ui.selectable_value(
-                                        &mut self.selected_weapon,
-                                        Some(weapon.clone()),
-                                        weapon,
-                                    );
+                            for (i, weapon) in weapons.iter().enumerate() {
+                                let is_highlighted = i == self.highlighted;
+
+                                let response =
+                                    Self::weapon_row(ui, weapon, is_highlighted, &mut history);
+
+                                if is_highlighted
+                                    && ui.input(|i| {
+                                        i.key_pressed(Key::ArrowDown) || i.key_pressed(Key::ArrowUp)
+                                    })
+                                {
+                                    response.scroll_to_me(None);
                                 }
-                            } else {
-                                for weapon in WEAPONS.iter() {
-                                    ui.selectable_value(
-                                        &mut self.selected_weapon,
-                                        Some(weapon.clone()),
-                                        weapon,
-                                    );
+
+                                if response.clicked() {
+                                    self.selected_weapon = Some(weapon.clone());
                                 }
                             }
                         });
                     });
                 });
             });
+
+        if let Some(selected_weapon) = &self.selected_weapon {
+            history.push_recent(selected_weapon);
+        }
+
+        if history != previous_history {
+            ui.ctx().data_mut(|data| {
+                data.insert_persisted(WeaponSelectHistory::id(), history);
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("rak gk", "Rakshasa's Great Katana").is_some());
+        assert!(fuzzy_score("clay", "Claymore").is_some());
+        assert!(fuzzy_score("zwei", "Claymore").is_none());
+        assert!(fuzzy_score("", "Claymore").is_some());
+
+        assert!(
+            fuzzy_score("gk", "Great Katana").unwrap()
+                > fuzzy_score("gk", "Gargoyle's Blackblade").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_push_recent() {
+        let mut history = WeaponSelectHistory::default();
+
+        for i in 0..15 {
+            history.push_recent(&format!("Weapon {}", i));
+        }
+
+        history.push_recent("Weapon 10");
+
+        assert_eq!(history.recents.len(), MAX_RECENTS);
+        assert_eq!(history.recents[0], "Weapon 10");
+        assert_eq!(history.recents[1], "Weapon 14");
+    }
+
+    #[test]
+    fn test_retain_known() {
+        let mut history = WeaponSelectHistory::default();
+
+        history.toggle_favourite("Claymore");
+        history.toggle_favourite("Renamed Sword");
+        history.push_recent("Renamed Sword");
+        history.push_recent("Dagger");
+
+        history.retain_known(|weapon| ["Claymore", "Dagger"].contains(&weapon));
+
+        assert_eq!(history.favourites, BTreeSet::from(["Claymore".to_string()]));
+        assert_eq!(history.recents, vec!["Dagger"]);
     }
 }